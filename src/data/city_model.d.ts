@@ -1277,6 +1277,7 @@ export namespace urbansynth {
     HOSPITAL = 5,
     PARK_POI = 6,
     FACTORY = 7,
+    CHARGING_STATION = 8,
   }
 
   /** BuildingType enum. */
@@ -1690,6 +1690,7 @@ export const urbansynth = $root.urbansynth = (() => {
                 case 5:
                 case 6:
                 case 7:
+                case 8:
                     break;
                 }
             if (message.position != null && message.hasOwnProperty("position")) {
@@ -1764,6 +1765,10 @@ export const urbansynth = $root.urbansynth = (() => {
             case 7:
                 message.type = 7;
                 break;
+            case "CHARGING_STATION":
+            case 8:
+                message.type = 8;
+                break;
             }
             if (object.position != null) {
                 if (typeof object.position !== "object")
@@ -3268,6 +3273,7 @@ export const urbansynth = $root.urbansynth = (() => {
      * @property {number} HOSPITAL=5 HOSPITAL value
      * @property {number} PARK_POI=6 PARK_POI value
      * @property {number} FACTORY=7 FACTORY value
+     * @property {number} CHARGING_STATION=8 CHARGING_STATION value
      */
     urbansynth.POIType = (function() {
         const valuesById = {}, values = Object.create(valuesById);
@@ -3279,6 +3285,7 @@ export const urbansynth = $root.urbansynth = (() => {
         values[valuesById[5] = "HOSPITAL"] = 5;
         values[valuesById[6] = "PARK_POI"] = 6;
         values[valuesById[7] = "FACTORY"] = 7;
+        values[valuesById[8] = "CHARGING_STATION"] = 8;
         return values;
     })();
 
@@ -175,6 +175,7 @@ export function useSimulation() {
 
   // Store initial agent state to prevent re-creating animation
   const initialAgentsRef = useRef<any[]>([]);
+  const lastFrameRef = useRef<number | null>(null);
 
   // Basic animation for fallback agents - create realistic movement patterns
   const startFallbackAnimation = useCallback(() => {
@@ -246,12 +247,18 @@ export function useSimulation() {
 
   // Update simulation (called every frame)
   const updateSimulation = useCallback(() => {
-    if (!state.isRunning) return;
+    if (!state.isRunning) {
+      lastFrameRef.current = null;
+      return;
+    }
 
     try {
       if (wasmModuleRef.current) {
-        // WASM mode - use real simulation
-        wasmModuleRef.current.tick();
+        // WASM mode - use real simulation, stepped by wall time so frame rate doesn't matter
+        const now = performance.now();
+        const elapsed = lastFrameRef.current === null ? 0 : now - lastFrameRef.current;
+        lastFrameRef.current = now;
+        wasmModuleRef.current.tick(elapsed);
 
         const agents = wasmModuleRef.current.getAgentStates();
         dispatch({ type: 'SET_AGENTS', payload: agents });
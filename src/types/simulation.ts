@@ -1,3 +1,5 @@
+import type { PerformanceProfile } from './performance';
+
 export interface Point2D {
   x: number;
   y: number;
@@ -9,14 +11,15 @@ export interface Agent {
   initialPosition?: Point2D; // Initial starting position for agent
   destination?: string;
   current_poi?: string;
-  agent_type: 'Pedestrian' | 'Car' | 'Bus' | 'Truck';
+  agent_type: 'Pedestrian' | 'Car' | 'Bus' | 'Truck' | 'Bicycle' | 'RideHail' | 'Autonomous';
+  owns_car?: boolean;
   schedule: ScheduleEntry[];
   current_schedule_index: number;
   speed: number;
   path: Point2D[];
   path_progress: number;
   needs: AgentNeeds;
-  state: 'Traveling' | 'AtDestination' | 'FindingPath' | 'Waiting';
+  state: 'Traveling' | 'AtDestination' | 'FindingPath' | 'Waiting' | 'AwaitingRide' | 'Riding' | 'Charging';
 }
 
 export interface ScheduleEntry {
@@ -86,6 +89,7 @@ export interface TrafficData {
   poi_popularity: Record<string, number>;
   flow_matrix: TrafficFlow[];
   congestion_points: CongestionPoint[];
+  mode_split: Record<string, number>; // Trips started per mode since the run began
 }
 
 export interface TrafficFlow {
@@ -122,12 +126,223 @@ export interface PerformanceMetrics {
   seed: number;
 }
 
+export interface AgentDelta {
+  frame: number; // Pass back as `since` on the next call
+  full: boolean;
+  ids: Uint32Array;
+  positions: Float32Array; // x, y pairs, same order as ids
+  headings: Float32Array;
+  speeds: Float32Array;
+  types: Uint8Array;
+  state_codes: Uint8Array;
+  spawned: Uint32Array;
+  despawned: Uint32Array;
+}
+
+export interface ViewBox {
+  min_x: number;
+  min_y: number;
+  max_x: number;
+  max_y: number;
+}
+
+export interface CameraView {
+  x: number;
+  y: number;
+}
+
+export interface AgentsInView {
+  ids: Uint32Array;
+  positions: Float32Array;
+  headings: Float32Array;
+  speeds: Float32Array;
+  types: Uint8Array;
+  state_codes: Uint8Array;
+  cluster_positions: Float32Array; // Centroid x, y pairs
+  cluster_counts: Uint32Array;
+  cluster_levels: Uint8Array;
+}
+
+export interface StateHash {
+  total: string; // 16 hex digits; the parts below narrow down where runs diverged
+  agents: string;
+  clock: string;
+  rng: string;
+  occupancy: string;
+}
+
+export interface HashLog {
+  interval: number;
+  steps: number;
+  entries: { step: number; sim_seconds: number; hash: string }[];
+  error?: string; // Why hashing stopped early, if it did
+}
+
+export interface ScalingEvent {
+  sim_seconds: number;
+  fps: number;
+  max_agents: [number, number]; // Before, after
+  update_frequency: [number, number];
+  render_distance: [number, number];
+}
+
+export interface TrajectoryFrame {
+  time: number; // Clock seconds
+  indices: number[]; // Agents that moved or changed state since the previous frame
+  positions: number[]; // x, y per index
+  states: number[]; // State codes, as in getAgentStateCodes()
+}
+
+export interface Trajectory {
+  interval_seconds: number;
+  agent_count: number;
+  frames: TrajectoryFrame[];
+}
+
+export interface LodStats {
+  micro_agents: number; // Traveling with full kinematics
+  meso_agents: number;  // Traveling link by link outside the area of interest
+}
+
+export interface SimDateTime {
+  year: number;
+  month: number;
+  day: number;
+  hour: number;
+  minute: number;
+  second: number;
+  weekday: number; // 0 = Monday
+  day_index: number; // Days since the simulation started
+  elapsed_seconds: number;
+}
+
+export interface SchedulerStats {
+  active_agents: number;
+  sleeping_agents: number;
+  queued_events: number;
+}
+
+export interface DayConvergence {
+  day: number;
+  trips: number;
+  mean_abs_error: number; // Hours between expected and experienced travel time
+  relative_gap: number;
+  switch_rate: number; // Share of repeated trips whose route or window changed
+  mean_travel_time: number; // Hours per trip
+}
+
+// One independent simulation with its own world, clock and RNG. Methods keep the snake_case
+// names wasm-bindgen exports; call free() when done with a handle.
+export interface SimulationHandle {
+  tick(elapsed_ms?: number): void;
+  fast_forward(hours: number): number; // Steps taken
+  get_scheduler_stats(): SchedulerStats;
+  state_hash(): StateHash;
+  start_hash_log(interval: number): void;
+  stop_hash_log(): HashLog | null;
+  get_hash_log(): HashLog | null;
+  get_interpolation_alpha(): number;
+  get_agent_states(): Agent[];
+  set_area_of_interest(x: number, y: number, radius: number): void;
+  clear_area_of_interest(): void;
+  get_lod_stats(): LodStats;
+  // Views into wasm memory, valid until the next call into the module; see syncAgentBuffers
+  sync_agent_buffers(): number;
+  get_agent_positions(): Float32Array;
+  get_agent_headings(): Float32Array;
+  get_agent_speeds(): Float32Array;
+  get_agent_types(): Uint8Array;
+  get_agent_state_codes(): Uint8Array;
+  get_changes_since(since: number): AgentDelta;
+  get_agents_in_view(view: ViewBox | CameraView, radius: number | undefined, lod: number): AgentsInView;
+  set_performance_profile(profile: PerformanceProfile): void;
+  enable_adaptive_scaling(profile: PerformanceProfile): void;
+  disable_adaptive_scaling(): void;
+  report_frame(fps: number, frame_ms: number): boolean;
+  get_scaling_log(): ScalingEvent[];
+  set_population_cap(cap?: number): void;
+  set_update_frequency(hz: number): void;
+  get_traffic_data(): TrafficData;
+  get_learning_convergence(): DayConvergence[];
+  get_emergency_state(): unknown; // EmergencyService in wasm/src/emergency.rs
+  get_response_time_stats(): unknown;
+  start(): void;
+  pause(): void;
+  set_speed(multiplier: number): void;
+  is_running(): boolean;
+  set_time_scale(seconds_per_real_second: number): void;
+  get_sim_datetime(): SimDateTime;
+  get_simulation_time(): number;
+  get_agent_count(): number;
+  get_seed(): bigint;
+  // Only in bundles built with the matching feature, see getFeatures()
+  load_plans?(plans: unknown): void; // scripting
+  save_state?(): Uint8Array; // networking
+  start_recording?(trajectory_interval_seconds?: number): void; // networking
+  stop_recording?(): Uint8Array; // networking
+  get_ride_hail_state?(): unknown; // economics
+  get_ride_hail_metrics?(): unknown; // economics
+  get_charging_stations?(): unknown; // economics
+  get_charging_demand?(): unknown; // economics
+  get_autonomous_state?(): unknown; // advanced-ai
+  get_drone_states?(): unknown[]; // physics
+  free(): void;
+}
+
+export interface SimulationHandleConstructor {
+  new (city: CityModel, seed?: bigint): SimulationHandle; // Seeded from the clock without one
+  from_state?(state: Uint8Array): SimulationHandle; // networking
+  from_recording?(recording: Uint8Array, ticks?: number): SimulationHandle; // networking
+}
+
 // WASM interface definitions
 export interface UrbanSynthSimModule {
+  // Any number of independent simulations; the functions below drive a single shared one
+  SimulationHandle: SimulationHandleConstructor;
   init(city_model_buffer: Uint8Array, config: any): void;
   init_with_seed(city_model_buffer: Uint8Array, config: any, seed: number): void;
-  tick(): void;
+  tick(elapsedMs?: number): void;
+  getInterpolationAlpha(): number;
   getAgentStates(): Agent[];
+  // Compact per-agent arrays viewing wasm memory; call syncAgentBuffers() first and use the
+  // views within the same frame, any later call into the module may invalidate them
+  syncAgentBuffers(): number;
+  getAgentPositions(): Float32Array; // x, y pairs
+  getAgentHeadings(): Float32Array;
+  getAgentSpeeds(): Float32Array;
+  getAgentTypes(): Uint8Array;
+  getAgentStateCodes(): Uint8Array;
+  // Agents changed after `since` (0 = all); drop `despawned`, upsert `ids`, reset when `full`
+  getChangesSince(since: number): AgentDelta;
+  // Culled to the view and render distance; agents at LOD 1+ come back merged into clusters
+  getAgentsInView(view: ViewBox | CameraView, radius: number | undefined, lod: number): AgentsInView;
+  // Full kinematics within `radius` meters of (x, y), a cheaper link-by-link model elsewhere
+  setAreaOfInterest(x: number, y: number, radius: number): void;
+  clearAreaOfInterest(): void;
+  getLodStats(): LodStats;
+  // Adaptive scaling: report every frame and the agent count, tick rate and render distance
+  // follow the frame rate; agents over the cap despawn once idle, and a cap above
+  // everyone there was spawns newcomers
+  enableAdaptiveScaling(profile: PerformanceProfile): void;
+  disableAdaptiveScaling(): void;
+  reportFrame(fps: number, frameMs: number): boolean;
+  getScalingLog(): ScalingEvent[];
+  setPopulationCap(cap?: number): void;
+  setUpdateFrequency(hz: number): void;
+  // Same inputs on any build or machine give the same hashes; throws if the state cannot be hashed
+  stateHash(): StateHash;
+  startHashLog(interval: number): void;
+  stopHashLog(): HashLog | null;
+  getHashLog(): HashLog | null;
+  // Snapshots (networking builds): the whole simulation state, restored exactly
+  saveState(): Uint8Array;
+  loadState(state: Uint8Array): void; // Replaces the running simulation
+  // Recordings (networking builds): a snapshot plus every input after it, which replays to
+  // the same state; with an interval, agent positions are sampled for playback as well
+  startRecording(trajectoryIntervalSeconds?: number): void;
+  stopRecording(): Uint8Array;
+  replayRecording(recording: Uint8Array, ticks?: number): void; // Replaces the running simulation
+  getRecordingTrajectory(recording: Uint8Array): Trajectory | null;
   getTrafficData(): TrafficData;
   updateWorld(event: WorldUpdateEvent): void;
   start(): void;
@@ -137,6 +352,7 @@ export interface UrbanSynthSimModule {
   getSimulationTime(): number;
   getAgentCount(): number;
   getSeed(): number;
+  getFeatures(): string[]; // Optional subsystems in this bundle, see wasm/Cargo.toml
   destroy(): void;
 }
 
@@ -167,6 +383,9 @@ export const AGENT_TYPES = {
   CAR: 'Car',
   BUS: 'Bus',
   TRUCK: 'Truck',
+  BICYCLE: 'Bicycle',
+  RIDE_HAIL: 'RideHail',
+  AUTONOMOUS: 'Autonomous',
 } as const;
 
 // Agent State constants
@@ -175,4 +394,7 @@ export const AGENT_STATES = {
   AT_DESTINATION: 'AtDestination',
   FINDING_PATH: 'FindingPath',
   WAITING: 'Waiting',
+  AWAITING_RIDE: 'AwaitingRide',
+  RIDING: 'Riding',
+  CHARGING: 'Charging',
 } as const;
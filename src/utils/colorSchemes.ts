@@ -25,6 +25,9 @@ export const DAY_COLORS = {
     bus: [255, 215, 0],
     truck: [160, 82, 45],
     pedestrian: [0, 191, 255],
+    bicycle: [50, 205, 50],
+    ridehail: [186, 85, 211],
+    autonomous: [0, 206, 209],
   },
 };
 
@@ -55,6 +58,9 @@ export const NIGHT_COLORS = {
     bus: [255, 255, 0],
     truck: [255, 165, 0],
     pedestrian: [173, 216, 230],
+    bicycle: [144, 238, 144],
+    ridehail: [221, 160, 221],
+    autonomous: [127, 255, 212],
   },
 };
 
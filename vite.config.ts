@@ -1,6 +1,11 @@
 import { defineConfig } from 'vite'
 import react from '@vitejs/plugin-react'
 
+const crossOriginIsolation = {
+  'Cross-Origin-Opener-Policy': 'same-origin',
+  'Cross-Origin-Embedder-Policy': 'require-corp',
+}
+
 export default defineConfig({
   plugins: [react()],
   base: '/citysim/',
@@ -36,7 +41,9 @@ export default defineConfig({
   server: {
     fs: {
       allow: ['..']
-    }
+    },
+    // Shared memory for the threaded wasm build (npm run build:wasm:threads) needs cross-origin isolation
+    headers: process.env.WASM_THREADS ? crossOriginIsolation : undefined,
   },
   define: {
     // Fix for deck.gl in production
@@ -0,0 +1,38 @@
+// Web Workers behind init_thread_pool (src/threads.rs). wasm-bindgen copies this file to
+// snippets/<crate>/js/ in the package, three levels below the generated module.
+
+function waitForMessage(target, type) {
+  return new Promise((resolve) => {
+    target.addEventListener('message', function onMessage({ data }) {
+      if (data?.type !== type) return;
+      target.removeEventListener('message', onMessage);
+      resolve(data);
+    });
+  });
+}
+
+// Inside a worker: load the module on the shared memory, report back, then run a rayon thread
+waitForMessage(self, 'pool_worker_init').then(async ({ module, memory, receiver }) => {
+  const pkg = await import('../../../urbansynth_sim.js');
+  await pkg.default({ module_or_path: module, memory });
+  postMessage({ type: 'pool_worker_ready' });
+  pkg.run_pool_worker(receiver);
+});
+
+export async function startWorkers(module, memory, builder) {
+  const threads = builder.num_threads();
+  if (threads === 0) {
+    throw new Error('init_thread_pool needs at least one thread');
+  }
+  const init = { type: 'pool_worker_init', module, memory, receiver: builder.receiver() };
+
+  await Promise.all(
+    Array.from({ length: threads }, async () => {
+      const worker = new Worker(new URL('./workers.js', import.meta.url), { type: 'module' });
+      worker.postMessage(init);
+      await waitForMessage(worker, 'pool_worker_ready');
+      return worker;
+    }),
+  );
+  builder.build();
+}
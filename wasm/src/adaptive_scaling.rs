@@ -1,8 +1,9 @@
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
-use web_sys::console;
+use crate::platform;
 use crate::performance::PerformanceProfile;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct AdaptiveScaler {
     current_profile: PerformanceProfile,
     current_fps: f64,
@@ -12,17 +13,20 @@ pub struct AdaptiveScaler {
     scaling_enabled: bool,
 }
 
-#[wasm_bindgen]
+impl Default for AdaptiveScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl AdaptiveScaler {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> AdaptiveScaler {
         let default_profile = PerformanceProfile::default();
         let target_fps = default_profile.target_fps() as f64;
 
-        console::log_2(
-            &"🎮 Adaptive scaler initialized with target FPS:".into(),
-            &target_fps.into()
-        );
+        platform::log(&format!("🎮 Adaptive scaler initialized with target FPS: {}", target_fps));
 
         AdaptiveScaler {
             target_fps,
@@ -34,14 +38,11 @@ impl AdaptiveScaler {
         }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn new_with_profile(profile: PerformanceProfile) -> AdaptiveScaler {
         let target_fps = profile.target_fps() as f64;
 
-        console::log_2(
-            &"🎮 Adaptive scaler initialized with target FPS:".into(),
-            &target_fps.into()
-        );
+        platform::log(&format!("🎮 Adaptive scaler initialized with target FPS: {}", target_fps));
 
         AdaptiveScaler {
             target_fps,
@@ -53,7 +54,7 @@ impl AdaptiveScaler {
         }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn update_fps(&mut self, fps: f64, delta_time: f64) {
         self.current_fps = fps;
         self.fps_history.push(fps);
@@ -82,7 +83,6 @@ impl AdaptiveScaler {
 
         // Only adjust if performance is stable (not during loading/transitions)
         if fps_stability < 0.7 {
-            console::log_1(&"⏸️ Skipping adjustment: FPS unstable".into());
             return;
         }
 
@@ -123,17 +123,17 @@ impl AdaptiveScaler {
             .max(100.0) as u32;
         self.current_profile.set_max_agents(new_agents);
 
-        // Also reduce render distance if agents are already quite low
+        // Also reduce render distance and tick rate if agents are already quite low
         if new_agents < 1000 {
-            let new_distance = self.current_profile.render_distance() * 0.9;
+            let new_distance = (self.current_profile.render_distance() * 0.9).max(200.0);
             self.current_profile.set_render_distance(new_distance);
+            let new_frequency = (self.current_profile.update_frequency() as f64 * reduction_factor).max(15.0) as u32;
+            self.current_profile.set_update_frequency(new_frequency);
         }
 
-        console::log_3(
-            &"📉 Scaling DOWN agents:".into(),
-            &old_agents.into(),
-            &format!(" → {}", new_agents).into()
-        );
+        if new_agents != old_agents {
+            platform::log(&format!("📉 Scaling DOWN agents: {} → {}", old_agents, new_agents));
+        }
     }
 
     fn scale_up_performance(&mut self) {
@@ -144,64 +144,64 @@ impl AdaptiveScaler {
             .min(100000.0) as u32;
         self.current_profile.set_max_agents(new_agents);
 
-        console::log_3(
-            &"📈 Scaling UP agents:".into(),
-            &old_agents.into(),
-            &format!(" → {}", new_agents).into()
-        );
+        // Win back tick rate given up earlier, up to one tick per frame
+        let new_frequency = ((self.current_profile.update_frequency() as f64 * increase_factor) as u32)
+            .min(self.current_profile.target_fps());
+        self.current_profile.set_update_frequency(new_frequency.max(self.current_profile.update_frequency()));
+
+        if new_agents != old_agents {
+            platform::log(&format!("📈 Scaling UP agents: {} → {}", old_agents, new_agents));
+        }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn set_scaling_enabled(&mut self, enabled: bool) {
         self.scaling_enabled = enabled;
-        console::log_2(
-            &"🎛️ Adaptive scaling:".into(),
-            &(if enabled { "enabled" } else { "disabled" }).into()
-        );
+        platform::log(&format!("🎛️ Adaptive scaling: {}", if enabled { "enabled" } else { "disabled" }));
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn force_profile_update(&mut self, max_agents: u32) {
         self.current_profile.set_max_agents(max_agents);
-        console::log_2(&"🔧 Manual agent count override:".into(), &max_agents.into());
+        platform::log(&format!("🔧 Manual agent count override: {}", max_agents));
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn get_current_profile(&self) -> PerformanceProfile {
         self.current_profile.clone()
     }
 
     // Getters for JavaScript integration
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn current_max_agents(&self) -> u32 { self.current_profile.max_agents() }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn current_fps(&self) -> f64 { self.current_fps }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn target_fps(&self) -> f64 { self.target_fps }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn fps_stability(&self) -> f64 { self.calculate_fps_stability() }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn render_distance(&self) -> f32 { self.current_profile.render_distance() }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn scaling_enabled(&self) -> bool { self.scaling_enabled }
 
     // Methods expected by lib.rs
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn set_target_fps(&mut self, fps: f64) {
         self.target_fps = fps;
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn update_fps_simple(&mut self, current_fps: f64) {
         self.update_fps(current_fps, 16.67); // Assume 60fps delta time
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn should_adapt(&self) -> bool {
         if !self.scaling_enabled || self.fps_history.len() < 30 {
             return false;
@@ -213,7 +213,7 @@ impl AdaptiveScaler {
         performance_gap.abs() > 0.15
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn get_adaptation_direction(&self) -> i32 {
         if !self.should_adapt() {
             return 0; // No adaptation needed
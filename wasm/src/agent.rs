@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use rand::prelude::*;
+use crate::clock::{self, SimClock};
+use crate::events::EventKind;
+use crate::learning::{TravelTimeMemory, TripRecord};
+use crate::pathfinding::advance_along_path;
+use crate::schedule::ScheduleTemplate;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Agent {
@@ -7,14 +12,25 @@ pub struct Agent {
     pub position: Point2D,
     pub destination: Option<String>,
     pub current_poi: Option<String>,
+    pub home_poi: Option<String>,
+    pub person_id: Option<String>, // Set when the agent follows an imported plan
+    pub owns_car: bool,
+    pub owns_av: bool,
+    pub is_ev: bool,
+    pub battery_soc: f32,
     pub agent_type: AgentType,
     pub schedule: Vec<ScheduleEntry>,
     pub current_schedule_index: usize,
     pub speed: f32,
+    pub speed_factor: f32,
+    pub platoon_id: Option<u32>,
     pub path: Vec<Point2D>,
     pub path_progress: f32,
     pub needs: AgentNeeds,
     pub state: AgentState,
+    pub travel_memory: TravelTimeMemory,
+    pub planned_trip: Option<PlannedTrip>,
+    pub active_trip: Option<TripRecord>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,24 +44,58 @@ impl Point2D {
     pub fn new(x: f32, y: f32) -> Self {
         Self { x, y }
     }
+
+    pub fn distance_to(&self, other: &Point2D) -> f32 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        (dx * dx + dy * dy).sqrt()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum AgentType {
     Pedestrian,
     Car,
     Bus,
     Truck,
+    Bicycle,
+    RideHail,
+    Autonomous,
+}
+
+impl AgentType {
+    /// Stable numeric id for compact exports, in declaration order.
+    pub fn code(&self) -> u8 {
+        match self {
+            AgentType::Pedestrian => 0,
+            AgentType::Car => 1,
+            AgentType::Bus => 2,
+            AgentType::Truck => 3,
+            AgentType::Bicycle => 4,
+            AgentType::RideHail => 5,
+            AgentType::Autonomous => 6,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduleEntry {
     pub poi_type: u32,
-    pub start_time: f32,
+    pub start_time: f32, // Hours past midnight of the schedule's day, 24 and up is the next day
     pub duration: f32,
     pub preferred_poi_id: Option<String>,
 }
 
+/// A trip that has been decided on but not started yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedTrip {
+    pub destination: String,
+    pub path: Vec<Point2D>,
+    pub mode: AgentType,
+    pub speed: f32,
+    pub record: TripRecord,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentNeeds {
     pub work: f32,
@@ -61,6 +111,24 @@ pub enum AgentState {
     AtDestination,
     FindingPath,
     Waiting,
+    AwaitingRide,
+    Riding,
+    Charging,
+}
+
+impl AgentState {
+    /// Stable numeric id for compact exports, in declaration order.
+    pub fn code(&self) -> u8 {
+        match self {
+            AgentState::Traveling => 0,
+            AgentState::AtDestination => 1,
+            AgentState::FindingPath => 2,
+            AgentState::Waiting => 3,
+            AgentState::AwaitingRide => 4,
+            AgentState::Riding => 5,
+            AgentState::Charging => 6,
+        }
+    }
 }
 
 impl Agent {
@@ -70,10 +138,18 @@ impl Agent {
             position: home_position.clone(),
             destination: None,
             current_poi: None,
+            home_poi: None,
+            person_id: None,
+            owns_car: true,
+            owns_av: false,
+            is_ev: false,
+            battery_soc: 1.0,
             agent_type: AgentType::Car,
             schedule: Vec::new(),
             current_schedule_index: 0,
             speed: 5.0,
+            speed_factor: 1.0,
+            platoon_id: None,
             path: Vec::new(),
             path_progress: 0.0,
             needs: AgentNeeds {
@@ -84,107 +160,141 @@ impl Agent {
                 home: 1.0,
             },
             state: AgentState::AtDestination,
+            travel_memory: TravelTimeMemory::default(),
+            planned_trip: None,
+            active_trip: None,
         }
     }
 
-    pub fn generate_daily_schedule(&mut self, rng: &mut impl Rng) {
-        self.schedule.clear();
-
-        // Morning routine: Home -> Work
-        self.schedule.push(ScheduleEntry {
-            poi_type: 1, // OFFICE
-            start_time: 8.0 + rng.gen::<f32>() * 2.0,
-            duration: 8.0,
-            preferred_poi_id: None,
-        });
-
-        // Lunch break
-        if rng.gen::<f32>() < 0.6 {
-            self.schedule.push(ScheduleEntry {
-                poi_type: 3, // RESTAURANT
-                start_time: 12.0 + rng.gen::<f32>() * 2.0,
-                duration: 1.0,
-                preferred_poi_id: None,
-            });
-        }
+    pub fn generate_daily_schedule(&mut self, template: &ScheduleTemplate, rng: &mut impl Rng) {
+        self.schedule = template.generate(rng);
+    }
 
-        // Evening: Work -> Shopping/Leisure -> Home
-        if rng.gen::<f32>() < 0.4 {
-            self.schedule.push(ScheduleEntry {
-                poi_type: 2, // SHOP
-                start_time: 17.0 + rng.gen::<f32>() * 2.0,
-                duration: 1.5,
-                preferred_poi_id: None,
-            });
-        }
+    /// `dt` is in simulated seconds, `clock` already advanced by it. `departure_lead` is how
+    /// long (hours) before a scheduled activity the agent starts planning the trip.
+    pub fn update(&mut self, dt: f32, clock: &SimClock, departure_lead: f32) {
+        self.update_needs(dt);
+        self.update_schedule(clock, departure_lead);
+        self.update_movement(dt);
+    }
 
-        if rng.gen::<f32>() < 0.3 {
-            self.schedule.push(ScheduleEntry {
-                poi_type: 6, // PARK_POI
-                start_time: 19.0 + rng.gen::<f32>() * 2.0,
-                duration: 2.0,
-                preferred_poi_id: None,
-            });
-        }
+    /// Applies the need decay missed while the agent was asleep in the event queue.
+    pub fn catch_up(&mut self, seconds: f32) {
+        self.update_needs(seconds);
+    }
 
-        // Return home
-        self.schedule.push(ScheduleEntry {
-            poi_type: 0, // HOME
-            start_time: 21.0 + rng.gen::<f32>() * 2.0,
-            duration: 10.0,
-            preferred_poi_id: None,
-        });
+    /// Idle agents have nothing to do until their next scheduled event.
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, AgentState::AtDestination | AgentState::Waiting)
     }
 
-    pub fn update(&mut self, dt: f32, current_time: f32) {
-        self.update_needs(dt);
-        self.update_schedule(current_time);
-        self.update_movement(dt);
+    /// Clock seconds at which an idle agent next needs attention.
+    pub fn next_event(&self, clock: &SimClock, departure_lead: f32) -> (f64, EventKind) {
+        if let AgentState::Waiting = self.state {
+            if let Some(trip) = &self.planned_trip {
+                return (trip.record.departure_time, EventKind::Departure);
+            }
+        }
+        match self.schedule.get(self.current_schedule_index) {
+            Some(entry) => (clock.at_hour(entry.start_time - departure_lead), EventKind::ActivityStart),
+            None => (clock.at_hour(24.0), EventKind::DayStart),
+        }
     }
 
     fn update_needs(&mut self, dt: f32) {
-        // Needs decay over time
-        self.needs.work = (self.needs.work - dt * 0.1).max(0.0);
-        self.needs.food = (self.needs.food - dt * 0.15).max(0.0);
-        self.needs.shopping = (self.needs.shopping - dt * 0.05).max(0.0);
-        self.needs.leisure = (self.needs.leisure - dt * 0.08).max(0.0);
-        self.needs.home = (self.needs.home - dt * 0.12).max(0.0);
+        // Needs decay over time, rates are per hour
+        let hours = clock::hours(dt);
+        self.needs.work = (self.needs.work - hours * 0.1).max(0.0);
+        self.needs.food = (self.needs.food - hours * 0.15).max(0.0);
+        self.needs.shopping = (self.needs.shopping - hours * 0.05).max(0.0);
+        self.needs.leisure = (self.needs.leisure - hours * 0.08).max(0.0);
+        self.needs.home = (self.needs.home - hours * 0.12).max(0.0);
     }
 
-    fn update_schedule(&mut self, current_time: f32) {
+    fn update_schedule(&mut self, clock: &SimClock, departure_lead: f32) {
+        let now = clock.elapsed_seconds;
+        match self.state {
+            AgentState::Traveling | AgentState::AwaitingRide | AgentState::Riding | AgentState::Charging => return,
+            AgentState::Waiting => {
+                let due = self.planned_trip.as_ref()
+                    .is_some_and(|trip| now >= trip.record.departure_time);
+                if due {
+                    if let Some(trip) = self.planned_trip.take() {
+                        self.begin_trip(trip, now);
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+
         if self.current_schedule_index < self.schedule.len() {
             let current_entry = &self.schedule[self.current_schedule_index];
-            if current_time >= current_entry.start_time {
+            if now >= clock.at_hour(current_entry.start_time - departure_lead) {
                 // Time to move to the next scheduled activity
                 self.state = AgentState::FindingPath;
             }
         }
     }
 
+    /// Starts a planned trip using the mode and route picked for it, at `now` clock seconds.
+    pub fn begin_trip(&mut self, trip: PlannedTrip, now: f64) {
+        self.destination = Some(trip.destination);
+        self.current_poi = None;
+        self.path = trip.path;
+        self.path_progress = 0.0;
+        self.agent_type = trip.mode;
+        self.speed = trip.speed;
+        self.active_trip = Some(TripRecord { departure_time: now, ..trip.record });
+        self.state = if matches!(trip.mode, AgentType::RideHail) {
+            // The fleet does the driving, we only wait for pickup
+            AgentState::AwaitingRide
+        } else {
+            AgentState::Traveling
+        };
+    }
+
+    /// Direction of travel in radians, 0 when not moving.
+    pub fn heading(&self) -> f32 {
+        match self.path.get(1) {
+            Some(next) => (next.y - self.position.y).atan2(next.x - self.position.x),
+            None => 0.0,
+        }
+    }
+
+    /// Called when a ride-hailing vehicle drops the agent off.
+    pub fn finish_ride(&mut self, position: Point2D) {
+        self.position = position;
+        self.path.clear();
+        self.path_progress = 0.0;
+        self.current_poi = self.destination.take();
+        self.state = AgentState::AtDestination;
+    }
+
+    /// Waits until the planned departure time before leaving.
+    pub fn plan_trip(&mut self, trip: PlannedTrip, now: f64) {
+        if trip.record.departure_time > now {
+            self.planned_trip = Some(trip);
+            self.state = AgentState::Waiting;
+        } else {
+            self.begin_trip(trip, now);
+        }
+    }
+
     fn update_movement(&mut self, dt: f32) {
-        match self.state {
-            AgentState::Traveling => {
-                if !self.path.is_empty() {
-                    self.path_progress += self.speed * dt;
-                    if self.path_progress >= 1.0 {
-                        // Move to next path segment
-                        self.path.remove(0);
-                        self.path_progress = 0.0;
-                        if self.path.is_empty() {
-                            self.state = AgentState::AtDestination;
-                        }
-                    } else {
-                        // Interpolate position along current path segment
-                        if self.path.len() > 1 {
-                            let start = &self.path[0];
-                            let end = &self.path[1];
-                            self.position.x = start.x + (end.x - start.x) * self.path_progress;
-                            self.position.y = start.y + (end.y - start.y) * self.path_progress;
-                        }
-                    }
-                }
-            }
-            _ => {}
+        if !matches!(self.state, AgentState::Traveling) {
+            return;
+        }
+
+        let distance = clock::distance_m(self.speed * self.speed_factor, dt);
+        advance_along_path(&mut self.path, &mut self.path_progress, &mut self.position, distance);
+
+        if self.path.len() <= 1 {
+            self.path.clear();
+            self.path_progress = 0.0;
+            self.current_poi = self.destination.take();
+            self.platoon_id = None;
+            self.state = AgentState::AtDestination;
         }
     }
-}
\ No newline at end of file
+}
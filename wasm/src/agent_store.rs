@@ -0,0 +1,45 @@
+use crate::agent::{Agent, AgentState};
+
+/// The per-frame fields a renderer needs, one array per field so they can be handed to
+/// JS as typed-array views without serializing anything. Index `i` in every array is
+/// `world.agents[i]`.
+#[derive(Debug, Clone, Default)]
+pub struct AgentStore {
+    pub positions: Vec<f32>,   // x, y per agent, meters
+    pub headings: Vec<f32>,    // Radians, 0 when not moving
+    pub speeds: Vec<f32>,      // km/h actually driven, 0 when not moving
+    pub agent_types: Vec<u8>,  // AgentType::code
+    pub states: Vec<u8>,       // AgentState::code
+}
+
+impl AgentStore {
+    /// Refills the arrays from the agents, reusing their allocations.
+    pub fn sync(&mut self, agents: &[Agent]) {
+        self.positions.clear();
+        self.headings.clear();
+        self.speeds.clear();
+        self.agent_types.clear();
+        self.states.clear();
+
+        for agent in agents {
+            self.push(agent);
+        }
+    }
+
+    pub fn push(&mut self, agent: &Agent) {
+        let moving = matches!(agent.state, AgentState::Traveling);
+        self.positions.extend([agent.position.x, agent.position.y]);
+        self.headings.push(if moving { agent.heading() } else { 0.0 });
+        self.speeds.push(if moving { agent.speed * agent.speed_factor } else { 0.0 });
+        self.agent_types.push(agent.agent_type.code());
+        self.states.push(agent.state.code());
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
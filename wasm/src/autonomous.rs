@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::agent::Point2D;
+use crate::clock;
+use crate::pathfinding::{advance_along_path, PathFinder};
+use crate::traffic::CongestionField;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutonomousConfig {
+    pub ownership_rate: f32,        // Share of car owners whose car drives itself
+    pub reposition_min_dwell: f32,  // Hours an activity must last before the car goes home to park
+    pub speed_kmh: f32,
+}
+
+impl Default for AutonomousConfig {
+    fn default() -> Self {
+        Self {
+            ownership_rate: 0.2,
+            reposition_min_dwell: 2.0,
+            speed_kmh: 30.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EmptyTripPurpose {
+    Park,   // Owner dropped off, drive home instead of paying for parking
+    Fetch,  // Owner wants to leave, come and pick them up
+}
+
+/// An AV driving around with nobody inside.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmptyTrip {
+    pub agent_id: u32,
+    pub purpose: EmptyTripPurpose,
+    pub position: Point2D,
+    pub path: Vec<Point2D>,
+    pub path_progress: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutonomousMetrics {
+    pub empty_trips: u32,
+    pub empty_km: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutonomousFleet {
+    pub config: AutonomousConfig,
+    pub empty_trips: Vec<EmptyTrip>,
+    pub parked: BTreeMap<u32, Point2D>, // Vehicles parked away from their owner
+    pub metrics: AutonomousMetrics,
+}
+
+impl AutonomousFleet {
+    pub fn vehicle_with_owner(&self, agent_id: u32) -> bool {
+        !self.parked.contains_key(&agent_id) && !self.empty_trips.iter().any(|t| t.agent_id == agent_id)
+    }
+
+    pub fn send_to_park(&mut self, agent_id: u32, from: &Point2D, parking: &Point2D, pathfinder: &PathFinder) {
+        self.metrics.empty_trips += 1;
+        self.empty_trips.push(EmptyTrip {
+            agent_id,
+            purpose: EmptyTripPurpose::Park,
+            position: from.clone(),
+            path: pathfinder.find_path(from, parking),
+            path_progress: 0.0,
+        });
+    }
+
+    /// Calls the vehicle back to its owner, turning it around if it is still on its way to park.
+    pub fn fetch(&mut self, agent_id: u32, owner_position: &Point2D, pathfinder: &PathFinder) {
+        if let Some(trip) = self.empty_trips.iter_mut().find(|t| t.agent_id == agent_id) {
+            if trip.purpose == EmptyTripPurpose::Park {
+                trip.purpose = EmptyTripPurpose::Fetch;
+                trip.path = pathfinder.find_path(&trip.position, owner_position);
+                trip.path_progress = 0.0;
+            }
+            return;
+        }
+
+        let Some(parked_at) = self.parked.remove(&agent_id) else { return };
+        self.metrics.empty_trips += 1;
+        self.empty_trips.push(EmptyTrip {
+            agent_id,
+            purpose: EmptyTripPurpose::Fetch,
+            path: pathfinder.find_path(&parked_at, owner_position),
+            position: parked_at,
+            path_progress: 0.0,
+        });
+    }
+
+    /// Moves empty vehicles and returns the owners whose car just arrived to pick them up.
+    pub fn update(&mut self, dt: f32, congestion: &CongestionField) -> Vec<u32> {
+        let mut arrived = Vec::new();
+
+        for trip in &mut self.empty_trips {
+            let before = trip.position.clone();
+            let distance = clock::distance_m(self.config.speed_kmh * congestion.speed_factor(&trip.position), dt);
+            advance_along_path(&mut trip.path, &mut trip.path_progress, &mut trip.position, distance);
+            self.metrics.empty_km += before.distance_to(&trip.position) / 1000.0;
+
+            if trip.path.len() <= 1 {
+                match trip.purpose {
+                    EmptyTripPurpose::Park => {
+                        self.parked.insert(trip.agent_id, trip.position.clone());
+                    }
+                    EmptyTripPurpose::Fetch => arrived.push(trip.agent_id),
+                }
+            }
+        }
+
+        self.empty_trips.retain(|trip| trip.path.len() > 1);
+        arrived
+    }
+}
@@ -1,9 +1,9 @@
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
-use web_sys::console;
-use js_sys::Date;
+use crate::platform;
 use crate::performance::PerformanceProfile;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct DeviceBenchmark {
     cpu_score: f64,
     memory_mb: f64,
@@ -12,9 +12,15 @@ pub struct DeviceBenchmark {
     gpu_tier: String,
 }
 
-#[wasm_bindgen]
+impl Default for DeviceBenchmark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl DeviceBenchmark {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> DeviceBenchmark {
         DeviceBenchmark {
             cpu_score: 0.0,
@@ -25,11 +31,11 @@ impl DeviceBenchmark {
         }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn run_cpu_benchmark(&mut self) -> f64 {
-        console::log_1(&"🎯 Running CPU benchmark for performance profiling...".into());
+        platform::log("🎯 Running CPU benchmark for performance profiling...");
 
-        let start = Date::now();
+        let start = platform::now_ms();
 
         // CPU-intensive test: prime number calculation + mathematical operations
         let mut primes_found = 0;
@@ -43,16 +49,12 @@ impl DeviceBenchmark {
             _computation_sum += (n as f64).sqrt() * (n as f64).sin();
         }
 
-        let duration = Date::now() - start;
+        let duration = platform::now_ms() - start;
 
         // Score based on operations per millisecond (higher is better)
         self.cpu_score = (primes_found as f64 * 100.0) / duration;
 
-        console::log_3(
-            &"✅ CPU benchmark completed. Score:".into(),
-            &self.cpu_score.into(),
-            &format!(" ({}ms)", duration).into()
-        );
+        platform::log(&format!("✅ CPU benchmark completed. Score: {} ({}ms)", self.cpu_score, duration));
 
         self.cpu_score
     }
@@ -60,34 +62,34 @@ impl DeviceBenchmark {
     fn is_prime(&self, n: u32) -> bool {
         if n < 2 { return false; }
         if n == 2 { return true; }
-        if n % 2 == 0 { return false; }
+        if n.is_multiple_of(2) { return false; }
 
         let sqrt_n = (n as f64).sqrt() as u32;
         for i in (3..=sqrt_n).step_by(2) {
-            if n % i == 0 { return false; }
+            if n.is_multiple_of(i) { return false; }
         }
         true
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn set_device_info(&mut self, refresh_rate: u32, is_mobile: bool, memory_mb: f64, gpu_tier: String) {
         self.refresh_rate = refresh_rate;
         self.is_mobile = is_mobile;
         self.memory_mb = memory_mb;
         self.gpu_tier = gpu_tier.clone();
 
-        console::log_1(&format!(
+        platform::log(&format!(
             "🖥️ Device capabilities: {}Hz, {} mobile, {:.0}MB RAM, {} GPU",
             refresh_rate,
             if is_mobile { "is" } else { "not" },
             memory_mb,
             gpu_tier
-        ).into());
+        ));
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn generate_profile(&self) -> PerformanceProfile {
-        console::log_1(&"🚀 Generating optimal performance profile...".into());
+        platform::log("🚀 Generating optimal performance profile...");
 
         let target_fps = self.calculate_target_fps();
         let max_agents = self.calculate_max_agents();
@@ -103,10 +105,10 @@ impl DeviceBenchmark {
         profile.set_lod_levels(lod_levels);
         profile.set_culling_enabled(culling_enabled);
 
-        console::log_1(&format!(
+        platform::log(&format!(
             "📊 Profile: {}fps, {} agents, {:.0}m distance, {} LOD levels",
             target_fps, max_agents, render_distance, lod_levels
-        ).into());
+        ));
 
         profile
     }
@@ -154,9 +156,9 @@ impl DeviceBenchmark {
 
         // Apply reasonable bounds
         if self.is_mobile {
-            calculated.max(500).min(2000)
+            calculated.clamp(500, 2000)
         } else {
-            calculated.max(1000).min(100000)
+            calculated.clamp(1000, 100000)
         }
     }
 
@@ -192,34 +194,34 @@ impl DeviceBenchmark {
     }
 
     // Getters for JavaScript
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn cpu_score(&self) -> f64 { self.cpu_score }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn refresh_rate(&self) -> u32 { self.refresh_rate }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn is_mobile(&self) -> bool { self.is_mobile }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn memory_mb(&self) -> f64 { self.memory_mb }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn gpu_tier(&self) -> String { self.gpu_tier.clone() }
 
     // Methods expected by lib.rs
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn run_cpu_test(&self) -> f64 {
         self.cpu_score
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn test_memory_bandwidth(&self) -> f64 {
         // Simple memory test score based on available memory
         (self.memory_mb / 1024.0) * 100.0 // Convert GB to score
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn get_recommended_profile(&self, cpu_score: f64, memory_mb: f64, refresh_rate: u32) -> PerformanceProfile {
         let mut benchmark = DeviceBenchmark::new();
         benchmark.cpu_score = cpu_score;
@@ -0,0 +1,267 @@
+//! Runs a city without a browser and writes the results to disk.
+//!
+//!     cargo run --release --no-default-features --features full --bin urbansynth-headless -- \
+//!         --city city.json --days 7 --seed 42 --out results/
+//!
+//! `--runs N` runs seeds S to S+N-1 instead and writes ensemble.json and ensemble.txt with
+//! means and 95% confidence intervals across them.
+//!
+//! `--hash-every N` also writes a state hash every N steps to state_hashes.json; two builds or
+//! machines given the same inputs should write identical files.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use urbansynth_sim::agent::AgentType;
+use urbansynth_sim::clock::SimDateTime;
+use urbansynth_sim::emergency::ZoneResponseStats;
+use urbansynth_sim::ensemble::{self, EnsembleConfig};
+use urbansynth_sim::features;
+use urbansynth_sim::learning::DayConvergence;
+use urbansynth_sim::simulation::Simulation;
+use urbansynth_sim::world::CityModel;
+#[cfg(feature = "physics")]
+use urbansynth_sim::drone::DroneMetrics;
+#[cfg(feature = "scripting")]
+use urbansynth_sim::plans::PlanSet;
+#[cfg(feature = "economics")]
+use urbansynth_sim::ride_hailing::RideHailMetrics;
+
+const USAGE: &str = "usage: urbansynth-headless --city <city.json> [--plans <plans.json>] \
+[--days N] [--seed S] [--runs N] [--out DIR] [--threads N] [--hash-every STEPS] [--no-snapshot]";
+
+struct Args {
+    city: PathBuf,
+    plans: Option<PathBuf>,
+    days: u32,
+    seed: u64,
+    runs: u32,
+    out: PathBuf,
+    #[cfg_attr(not(feature = "networking"), allow(dead_code))]
+    snapshot: bool,
+    threads: Option<usize>, // Worker threads with the parallel feature, all cores by default
+    hash_every: Option<u64>, // Steps between state hashes in state_hashes.json
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut city = None;
+        let mut plans = None;
+        let mut days = 1;
+        let mut seed = 0;
+        let mut runs = 1;
+        let mut out = PathBuf::from("results");
+        let mut snapshot = true;
+        let mut threads = None;
+        let mut hash_every = None;
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--city" => city = Some(PathBuf::from(value()?)),
+                "--plans" => plans = Some(PathBuf::from(value()?)),
+                "--days" => days = value()?.parse().map_err(|_| "--days must be a whole number".to_string())?,
+                "--seed" => seed = value()?.parse().map_err(|_| "--seed must be a whole number".to_string())?,
+                "--runs" => runs = value()?.parse().map_err(|_| "--runs must be a whole number".to_string())?,
+                "--out" => out = PathBuf::from(value()?),
+                "--threads" => threads = Some(value()?.parse().map_err(|_| "--threads must be a whole number".to_string())?),
+                "--hash-every" => hash_every = Some(value()?.parse().map_err(|_| "--hash-every must be a whole number".to_string())?),
+                "--no-snapshot" => snapshot = false,
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                other => return Err(format!("unknown argument {}\n{}", other, USAGE)),
+            }
+        }
+
+        let city = city.ok_or_else(|| USAGE.to_string())?;
+        Ok(Self { city, plans, days, seed, runs, out, snapshot, threads, hash_every })
+    }
+}
+
+#[derive(Serialize)]
+struct DaySummary {
+    day: u32,
+    steps: u32,
+    mode_split: BTreeMap<AgentType, u32>, // Trips started during this day
+    congestion_points: usize,
+    wall_ms: f64,
+}
+
+#[derive(Serialize)]
+struct RunSummary {
+    city: String,
+    features: Vec<&'static str>,
+    seed: u64,
+    days: u32,
+    agents: u32,
+    ended_at: SimDateTime,
+    state_hash: String,
+    wall_ms: f64,
+    daily: Vec<DaySummary>,
+    convergence: Vec<DayConvergence>,
+    emergency_response: BTreeMap<String, ZoneResponseStats>,
+    #[cfg(feature = "economics")]
+    ride_hailing: RideHailMetrics,
+    #[cfg(feature = "economics")]
+    charging_detours: u32,
+    #[cfg(feature = "physics")]
+    drones: DroneMetrics,
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| format!("Failed to encode {}: {}", path.display(), e))?;
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    if let Some(threads) = args.threads {
+        #[cfg(feature = "parallel")]
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|e| format!("Failed to start {} worker threads: {}", threads, e))?;
+        #[cfg(not(feature = "parallel"))]
+        if threads != 1 {
+            return Err("--threads needs a build with the parallel feature".to_string());
+        }
+    }
+
+    let city: CityModel = read_json(&args.city)?;
+    if args.runs > 1 {
+        return run_ensemble(args, &city);
+    }
+
+    let mut simulation = Simulation::new_with_seed(args.seed);
+    simulation.init_with_seed(city, args.seed);
+    if let Some(path) = &args.plans {
+        load_plans(&mut simulation, path)?;
+    }
+    // Keep the charging demand of the whole run for charging_demand.json
+    #[cfg(feature = "economics")]
+    {
+        let charging = &mut simulation.world.charging.config;
+        charging.history_intervals = (args.days as f32 * 24.0 / charging.sample_interval_hours).ceil() as u32 + 1;
+    }
+    simulation.start();
+    if let Some(interval) = args.hash_every {
+        simulation.start_hash_log(interval);
+    }
+
+    eprintln!("Simulating {} day(s) of {} agents with seed {}", args.days, simulation.get_agent_count(), args.seed);
+
+    let started = Instant::now();
+    let mut daily = Vec::with_capacity(args.days as usize);
+    let mut trips_so_far: BTreeMap<AgentType, u32> = BTreeMap::new();
+    for day in 0..args.days {
+        let day_started = Instant::now();
+        let steps = simulation.fast_forward(24.0);
+        let traffic = simulation.get_traffic_data();
+        let wall_ms = day_started.elapsed().as_secs_f64() * 1000.0;
+        eprintln!("  day {}: {} steps in {:.0}ms", day + 1, steps, wall_ms);
+
+        // The simulation counts trips since the run began
+        let mode_split = traffic.mode_split.iter()
+            .map(|(mode, &trips)| (*mode, trips - trips_so_far.get(mode).copied().unwrap_or(0)))
+            .collect();
+        trips_so_far = traffic.mode_split;
+
+        daily.push(DaySummary {
+            day,
+            steps,
+            mode_split,
+            congestion_points: traffic.congestion_points.len(),
+            wall_ms,
+        });
+    }
+
+    let summary = RunSummary {
+        city: args.city.display().to_string(),
+        features: features::enabled(),
+        seed: args.seed,
+        days: args.days,
+        agents: simulation.get_agent_count(),
+        ended_at: simulation.get_datetime(),
+        state_hash: simulation.state_hash()?.total,
+        wall_ms: started.elapsed().as_secs_f64() * 1000.0,
+        daily,
+        convergence: simulation.get_learning_convergence().to_vec(),
+        emergency_response: simulation.get_emergency_state().response_stats.clone(),
+        #[cfg(feature = "economics")]
+        ride_hailing: simulation.get_ride_hail_state().metrics.clone(),
+        #[cfg(feature = "economics")]
+        charging_detours: simulation.get_charging_state().detours,
+        #[cfg(feature = "physics")]
+        drones: simulation.world.drones.metrics.clone(),
+    };
+
+    std::fs::create_dir_all(&args.out).map_err(|e| format!("Failed to create {}: {}", args.out.display(), e))?;
+    write_json(&args.out.join("summary.json"), &summary)?;
+    #[cfg(feature = "economics")]
+    write_json(&args.out.join("charging_demand.json"), &simulation.get_charging_state().demand_series)?;
+    if let Some(log) = simulation.stop_hash_log() {
+        write_json(&args.out.join("state_hashes.json"), &log)?;
+        if let Some(error) = log.error {
+            return Err(error);
+        }
+    }
+    #[cfg(feature = "networking")]
+    if args.snapshot {
+        let path = args.out.join("final_state.bin");
+        std::fs::write(&path, simulation.save_state()?).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    eprintln!("Wrote results to {}", args.out.display());
+    Ok(())
+}
+
+#[cfg(feature = "scripting")]
+fn load_plans(simulation: &mut Simulation, path: &Path) -> Result<(), String> {
+    simulation.load_plans(&read_json::<PlanSet>(path)?)
+}
+
+#[cfg(not(feature = "scripting"))]
+fn load_plans(_simulation: &mut Simulation, path: &Path) -> Result<(), String> {
+    Err(format!("Cannot load {}: built without the scripting feature", path.display()))
+}
+
+fn run_ensemble(args: &Args, city: &CityModel) -> Result<(), String> {
+    let config = EnsembleConfig {
+        runs: args.runs,
+        base_seed: args.seed,
+        days: args.days,
+        ..EnsembleConfig::default()
+    };
+    eprintln!("Simulating {} day(s) under {} seeds from {}", args.days, args.runs, args.seed);
+
+    let started = Instant::now();
+    let report = ensemble::run_ensemble(city, &config, |simulation| match &args.plans {
+        Some(path) => load_plans(simulation, path),
+        None => Ok(()),
+    })?;
+    eprintln!("  {} runs in {:.0}ms", report.runs.len(), started.elapsed().as_secs_f64() * 1000.0);
+
+    let summary = report.summary(10);
+    eprintln!("\n{}", summary);
+    std::fs::create_dir_all(&args.out).map_err(|e| format!("Failed to create {}: {}", args.out.display(), e))?;
+    write_json(&args.out.join("ensemble.json"), &report)?;
+    let path = args.out.join("ensemble.txt");
+    std::fs::write(&path, summary).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    eprintln!("Wrote results to {}", args.out.display());
+    Ok(())
+}
+
+fn main() {
+    let result = Args::parse().and_then(|args| run(&args));
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
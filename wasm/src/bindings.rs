@@ -0,0 +1,828 @@
+//! JavaScript API. Only built with the `wasm` feature; the native runner uses the
+//! modules directly. Exports for optional subsystems exist only in builds that include
+//! them, so JS should check `get_features()` before calling them.
+
+use wasm_bindgen::prelude::*;
+use serde_wasm_bindgen::to_value;
+use js_sys::{Float32Array, Object, Reflect, Uint32Array, Uint8Array};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::agent_store::AgentStore;
+use crate::culling::{AgentsInView, View};
+use crate::delta::AgentDelta;
+use crate::meso::AreaOfInterest;
+use crate::simulation::Simulation;
+use crate::world::CityModel;
+#[cfg(feature = "scripting")]
+use crate::plans::PlanSet;
+#[cfg(feature = "networking")]
+use crate::replay::Recording;
+use crate::performance::PerformanceProfile;
+use crate::benchmarking::DeviceBenchmark;
+use crate::adaptive_scaling::AdaptiveScaler;
+
+// The free functions below drive this default instance; create `SimulationHandle`s
+// directly to run several cities side by side
+thread_local! {
+    static SIMULATION: Rc<RefCell<Option<SimulationHandle>>> = Rc::new(RefCell::new(None));
+}
+
+fn with_simulation<T>(default: T, f: impl FnOnce(&SimulationHandle) -> T) -> T {
+    SIMULATION.with(|sim| match *sim.borrow() {
+        Some(ref handle) => f(handle),
+        None => default,
+    })
+}
+
+fn with_simulation_mut<T>(default: T, f: impl FnOnce(&mut SimulationHandle) -> T) -> T {
+    SIMULATION.with(|sim| match *sim.borrow_mut() {
+        Some(ref mut handle) => f(handle),
+        None => default,
+    })
+}
+
+fn install(handle: SimulationHandle) {
+    SIMULATION.with(|sim| {
+        *sim.borrow_mut() = Some(handle);
+    });
+}
+
+fn not_initialized<T>() -> Result<T, JsValue> {
+    Err(JsValue::from_str("Simulation not initialized"))
+}
+
+fn js_error(message: String) -> JsValue {
+    JsValue::from_str(&message)
+}
+
+fn js_object(fields: Vec<(&str, JsValue)>) -> JsValue {
+    let object = Object::new();
+    for (key, value) in fields {
+        let _ = Reflect::set(&object, &JsValue::from_str(key), &value);
+    }
+    object.into()
+}
+
+/// Copies of the compact arrays, named like the `get_agent_*` views.
+fn store_fields(store: &AgentStore) -> Vec<(&'static str, JsValue)> {
+    vec![
+        ("positions", Float32Array::from(&store.positions[..]).into()),
+        ("headings", Float32Array::from(&store.headings[..]).into()),
+        ("speeds", Float32Array::from(&store.speeds[..]).into()),
+        ("types", Uint8Array::from(&store.agent_types[..]).into()),
+        ("state_codes", Uint8Array::from(&store.states[..]).into()),
+    ]
+}
+
+fn delta_to_js(delta: &AgentDelta) -> JsValue {
+    let mut fields = vec![
+        ("frame", delta.frame.into()),
+        ("full", delta.full.into()),
+        ("ids", Uint32Array::from(&delta.ids[..]).into()),
+        ("spawned", Uint32Array::from(&delta.spawned[..]).into()),
+        ("despawned", Uint32Array::from(&delta.despawned[..]).into()),
+    ];
+    fields.extend(store_fields(&delta.agents));
+    js_object(fields)
+}
+
+fn view_to_js(view: &AgentsInView) -> JsValue {
+    let mut fields = vec![
+        ("ids", Uint32Array::from(&view.ids[..]).into()),
+        ("cluster_positions", Float32Array::from(&view.cluster_positions[..]).into()),
+        ("cluster_counts", Uint32Array::from(&view.cluster_counts[..]).into()),
+        ("cluster_levels", Uint8Array::from(&view.cluster_levels[..]).into()),
+    ];
+    fields.extend(store_fields(&view.agents));
+    js_object(fields)
+}
+
+#[wasm_bindgen(start)]
+pub fn main() {
+    console_error_panic_hook::set_once();
+}
+
+/// One independent simulation. Every handle owns its own world, clock and RNG.
+#[wasm_bindgen]
+pub struct SimulationHandle {
+    inner: Simulation,
+}
+
+#[wasm_bindgen]
+impl SimulationHandle {
+    /// Without a seed the current time is used.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &JsValue, seed: Option<u64>) -> Result<SimulationHandle, JsValue> {
+        // Parse city model from JSON config
+        let city_data: CityModel = serde_wasm_bindgen::from_value(config.clone())?;
+
+        let inner = match seed {
+            Some(seed) => {
+                let mut simulation = Simulation::new_with_seed(seed);
+                simulation.init_with_seed(city_data, seed);
+                simulation
+            }
+            None => {
+                let mut simulation = Simulation::new();
+                simulation.init(city_data);
+                simulation
+            }
+        };
+
+        Ok(SimulationHandle { inner })
+    }
+
+    pub fn tick(&mut self, elapsed_ms: Option<f64>) {
+        match elapsed_ms {
+            Some(elapsed_ms) => {
+                self.inner.tick_elapsed(elapsed_ms);
+            }
+            None => self.inner.tick(),
+        }
+    }
+
+    pub fn fast_forward(&mut self, hours: f32) -> u32 {
+        self.inner.fast_forward(hours)
+    }
+
+    pub fn get_scheduler_stats(&self) -> JsValue {
+        to_value(&self.inner.get_scheduler_stats()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Hex fingerprints of the state; compare with a native or replayed run of the same inputs.
+    pub fn state_hash(&self) -> Result<JsValue, JsValue> {
+        Ok(to_value(&self.inner.state_hash().map_err(js_error)?)?)
+    }
+
+    pub fn start_hash_log(&mut self, interval: u32) {
+        self.inner.start_hash_log(interval as u64);
+    }
+
+    pub fn stop_hash_log(&mut self) -> JsValue {
+        to_value(&self.inner.stop_hash_log()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_hash_log(&self) -> JsValue {
+        to_value(&self.inner.get_hash_log()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.inner.get_interpolation_alpha()
+    }
+
+    pub fn get_agent_states(&self) -> JsValue {
+        to_value(&self.inner.get_agent_states()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Agents within `radius` meters of (x, y) get full kinematics; the rest advance link by
+    /// link until the area comes back to them.
+    pub fn set_area_of_interest(&mut self, x: f32, y: f32, radius: f32) {
+        self.inner.set_area_of_interest(Some(AreaOfInterest { x, y, radius }));
+    }
+
+    pub fn clear_area_of_interest(&mut self) {
+        self.inner.set_area_of_interest(None);
+    }
+
+    pub fn get_lod_stats(&self) -> JsValue {
+        to_value(&self.inner.get_lod_stats()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Refreshes the compact per-agent arrays and returns the agent count. The
+    /// `get_agent_*` arrays after it are views straight into wasm memory (positions as
+    /// x, y pairs in meters, headings in radians, speeds in km/h, `AgentType` and
+    /// `AgentState` codes). A view is only valid until the next call into the module,
+    /// since any allocation can move or grow the memory, so sync, then read or upload
+    /// them within the same frame.
+    pub fn sync_agent_buffers(&mut self) -> u32 {
+        self.inner.sync_agent_store().len() as u32
+    }
+
+    /// Only the agents that changed after frame `since` (0 for all of them), as
+    /// `{ frame, full, ids, positions, headings, speeds, types, state_codes, spawned,
+    /// despawned }`. Remove `despawned` ids, then upsert each id; when `full` is set,
+    /// start over from this delta alone. Pass `frame` back as `since` next time.
+    pub fn get_changes_since(&mut self, since: u32) -> JsValue {
+        delta_to_js(&self.inner.get_changes_since(since))
+    }
+
+    /// Agents to draw for a view, either `{ min_x, min_y, max_x, max_y }` or the camera's
+    /// ground point `{ x, y }` with `radius` (defaults to the profile's render distance).
+    /// Returns `{ ids, positions, headings, speeds, types, state_codes }` for agents shown
+    /// individually plus `{ cluster_positions, cluster_counts, cluster_levels }` for those
+    /// merged at LOD 1 and up. `lod` is the finest level wanted, e.g. from the zoom.
+    pub fn get_agents_in_view(&mut self, view: &JsValue, radius: Option<f32>, lod: u32) -> Result<JsValue, JsValue> {
+        let view: View = serde_wasm_bindgen::from_value(view.clone())?;
+        Ok(view_to_js(&self.inner.get_agents_in_view(&view, radius, lod)))
+    }
+
+    /// Render distance, culling and LOD settings used by `get_agents_in_view`.
+    pub fn set_performance_profile(&mut self, profile: &PerformanceProfile) {
+        self.inner.set_performance_profile(profile.clone());
+    }
+
+    /// From here on `report_frame` adjusts the agent count, tick rate and render distance.
+    pub fn enable_adaptive_scaling(&mut self, profile: &PerformanceProfile) {
+        self.inner.enable_adaptive_scaling(profile.clone());
+    }
+
+    pub fn disable_adaptive_scaling(&mut self) {
+        self.inner.disable_adaptive_scaling();
+    }
+
+    /// Call once per rendered frame; returns true when the simulation was adjusted.
+    pub fn report_frame(&mut self, fps: f64, frame_ms: f64) -> bool {
+        self.inner.report_frame(fps, frame_ms)
+    }
+
+    pub fn get_scaling_log(&self) -> JsValue {
+        to_value(self.inner.get_scaling_log()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn set_population_cap(&mut self, cap: Option<u32>) {
+        self.inner.set_population_cap(cap);
+    }
+
+    pub fn set_update_frequency(&mut self, hz: u32) {
+        self.inner.set_update_frequency(hz);
+    }
+
+    pub fn get_agent_positions(&self) -> Float32Array {
+        // SAFETY: see the note on `sync_agent_buffers`
+        unsafe { Float32Array::view(&self.inner.get_agent_store().positions) }
+    }
+
+    pub fn get_agent_headings(&self) -> Float32Array {
+        // SAFETY: see the note on `sync_agent_buffers`
+        unsafe { Float32Array::view(&self.inner.get_agent_store().headings) }
+    }
+
+    pub fn get_agent_speeds(&self) -> Float32Array {
+        // SAFETY: see the note on `sync_agent_buffers`
+        unsafe { Float32Array::view(&self.inner.get_agent_store().speeds) }
+    }
+
+    pub fn get_agent_types(&self) -> Uint8Array {
+        // SAFETY: see the note on `sync_agent_buffers`
+        unsafe { Uint8Array::view(&self.inner.get_agent_store().agent_types) }
+    }
+
+    pub fn get_agent_state_codes(&self) -> Uint8Array {
+        // SAFETY: see the note on `sync_agent_buffers`
+        unsafe { Uint8Array::view(&self.inner.get_agent_store().states) }
+    }
+
+    pub fn get_traffic_data(&self) -> JsValue {
+        to_value(&self.inner.get_traffic_data()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_learning_convergence(&self) -> JsValue {
+        to_value(self.inner.get_learning_convergence()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_emergency_state(&self) -> JsValue {
+        to_value(self.inner.get_emergency_state()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_response_time_stats(&self) -> JsValue {
+        to_value(&self.inner.get_emergency_state().response_stats).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn start(&mut self) {
+        self.inner.start();
+    }
+
+    pub fn pause(&mut self) {
+        self.inner.pause();
+    }
+
+    pub fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    pub fn set_time_scale(&mut self, seconds_per_real_second: f64) {
+        self.inner.set_time_scale(seconds_per_real_second);
+    }
+
+    pub fn get_sim_datetime(&self) -> JsValue {
+        to_value(&self.inner.get_datetime()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_simulation_time(&self) -> f32 {
+        self.inner.get_time()
+    }
+
+    pub fn get_agent_count(&self) -> u32 {
+        self.inner.get_agent_count()
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.inner.get_seed()
+    }
+}
+
+// Imported activity plans
+#[cfg(feature = "scripting")]
+#[wasm_bindgen]
+impl SimulationHandle {
+    pub fn load_plans(&mut self, plans: &JsValue) -> Result<(), JsValue> {
+        let plan_set: PlanSet = serde_wasm_bindgen::from_value(plans.clone())?;
+        self.inner.load_plans(&plan_set).map_err(js_error)
+    }
+}
+
+// Snapshots and recordings
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+impl SimulationHandle {
+    pub fn from_state(blob: &[u8]) -> Result<SimulationHandle, JsValue> {
+        let inner = Simulation::load_state(blob).map_err(js_error)?;
+        Ok(SimulationHandle { inner })
+    }
+
+    pub fn from_recording(blob: &[u8], ticks: Option<u32>) -> Result<SimulationHandle, JsValue> {
+        let recording = Recording::decode(blob).map_err(js_error)?;
+        let inner = Simulation::replay(&recording, ticks.map(|t| t as usize)).map_err(js_error)?;
+        Ok(SimulationHandle { inner })
+    }
+
+    pub fn save_state(&self) -> Result<Vec<u8>, JsValue> {
+        self.inner.save_state().map_err(js_error)
+    }
+
+    pub fn start_recording(&mut self, trajectory_interval_seconds: Option<f64>) -> Result<(), JsValue> {
+        self.inner.start_recording(trajectory_interval_seconds).map_err(js_error)
+    }
+
+    pub fn stop_recording(&mut self) -> Result<Vec<u8>, JsValue> {
+        match self.inner.stop_recording() {
+            Some(recording) => recording.encode().map_err(js_error),
+            None => Err(JsValue::from_str("Not recording")),
+        }
+    }
+}
+
+// Ride-hailing and EV charging
+#[cfg(feature = "economics")]
+#[wasm_bindgen]
+impl SimulationHandle {
+    pub fn get_ride_hail_state(&self) -> JsValue {
+        to_value(self.inner.get_ride_hail_state()).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_ride_hail_metrics(&self) -> JsValue {
+        to_value(&self.inner.get_ride_hail_state().metrics).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_charging_stations(&self) -> JsValue {
+        to_value(&self.inner.get_charging_state().stations).unwrap_or(JsValue::NULL)
+    }
+
+    pub fn get_charging_demand(&self) -> JsValue {
+        to_value(&self.inner.get_charging_state().demand_series).unwrap_or(JsValue::NULL)
+    }
+}
+
+// Self-driving cars
+#[cfg(feature = "advanced-ai")]
+#[wasm_bindgen]
+impl SimulationHandle {
+    pub fn get_autonomous_state(&self) -> JsValue {
+        to_value(self.inner.get_autonomous_state()).unwrap_or(JsValue::NULL)
+    }
+}
+
+// Drones
+#[cfg(feature = "physics")]
+#[wasm_bindgen]
+impl SimulationHandle {
+    pub fn get_drone_states(&self) -> JsValue {
+        to_value(&self.inner.get_drone_states()).unwrap_or(JsValue::NULL)
+    }
+}
+
+/// Optional subsystems compiled into this bundle, e.g. `["economics", "physics"]`.
+#[wasm_bindgen]
+pub fn get_features() -> JsValue {
+    to_value(&crate::features::enabled()).unwrap_or(JsValue::NULL)
+}
+
+// Compatibility layer: the original single-simulation API
+
+#[wasm_bindgen]
+pub fn init(config: &JsValue) -> Result<(), JsValue> {
+    install(SimulationHandle::new(config, None)?);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn init_with_seed(config: &JsValue, seed: u64) -> Result<(), JsValue> {
+    install(SimulationHandle::new(config, Some(seed))?);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn tick(elapsed_ms: Option<f64>) {
+    with_simulation_mut((), |handle| handle.tick(elapsed_ms))
+}
+
+#[wasm_bindgen]
+pub fn fast_forward(hours: f32) -> u32 {
+    with_simulation_mut(0, |handle| handle.fast_forward(hours))
+}
+
+#[wasm_bindgen]
+pub fn get_scheduler_stats() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_scheduler_stats())
+}
+
+#[wasm_bindgen]
+pub fn state_hash() -> Result<JsValue, JsValue> {
+    with_simulation(not_initialized(), |handle| handle.state_hash())
+}
+
+#[wasm_bindgen]
+pub fn start_hash_log(interval: u32) {
+    with_simulation_mut((), |handle| handle.start_hash_log(interval))
+}
+
+#[wasm_bindgen]
+pub fn stop_hash_log() -> JsValue {
+    with_simulation_mut(JsValue::NULL, |handle| handle.stop_hash_log())
+}
+
+#[wasm_bindgen]
+pub fn get_hash_log() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_hash_log())
+}
+
+#[wasm_bindgen]
+pub fn get_interpolation_alpha() -> f32 {
+    with_simulation(0.0, |handle| handle.get_interpolation_alpha())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_states() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_agent_states())
+}
+
+#[wasm_bindgen]
+pub fn get_changes_since(since: u32) -> JsValue {
+    with_simulation_mut(JsValue::NULL, |handle| handle.get_changes_since(since))
+}
+
+#[wasm_bindgen]
+pub fn get_agents_in_view(view: &JsValue, radius: Option<f32>, lod: u32) -> Result<JsValue, JsValue> {
+    with_simulation_mut(not_initialized(), |handle| handle.get_agents_in_view(view, radius, lod))
+}
+
+#[wasm_bindgen]
+pub fn set_area_of_interest(x: f32, y: f32, radius: f32) {
+    with_simulation_mut((), |handle| handle.set_area_of_interest(x, y, radius))
+}
+
+#[wasm_bindgen]
+pub fn clear_area_of_interest() {
+    with_simulation_mut((), |handle| handle.clear_area_of_interest())
+}
+
+#[wasm_bindgen]
+pub fn get_lod_stats() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_lod_stats())
+}
+
+#[wasm_bindgen]
+pub fn set_performance_profile(profile: &PerformanceProfile) {
+    with_simulation_mut((), |handle| handle.set_performance_profile(profile))
+}
+
+#[wasm_bindgen]
+pub fn enable_adaptive_scaling(profile: &PerformanceProfile) {
+    with_simulation_mut((), |handle| handle.enable_adaptive_scaling(profile))
+}
+
+#[wasm_bindgen]
+pub fn disable_adaptive_scaling() {
+    with_simulation_mut((), |handle| handle.disable_adaptive_scaling())
+}
+
+#[wasm_bindgen]
+pub fn report_frame(fps: f64, frame_ms: f64) -> bool {
+    with_simulation_mut(false, |handle| handle.report_frame(fps, frame_ms))
+}
+
+#[wasm_bindgen]
+pub fn get_scaling_log() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_scaling_log())
+}
+
+#[wasm_bindgen]
+pub fn set_population_cap(cap: Option<u32>) {
+    with_simulation_mut((), |handle| handle.set_population_cap(cap))
+}
+
+#[wasm_bindgen]
+pub fn set_update_frequency(hz: u32) {
+    with_simulation_mut((), |handle| handle.set_update_frequency(hz))
+}
+
+#[wasm_bindgen]
+pub fn sync_agent_buffers() -> u32 {
+    with_simulation_mut(0, |handle| handle.sync_agent_buffers())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_positions() -> Float32Array {
+    with_simulation(Float32Array::new_with_length(0), |handle| handle.get_agent_positions())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_headings() -> Float32Array {
+    with_simulation(Float32Array::new_with_length(0), |handle| handle.get_agent_headings())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_speeds() -> Float32Array {
+    with_simulation(Float32Array::new_with_length(0), |handle| handle.get_agent_speeds())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_types() -> Uint8Array {
+    with_simulation(Uint8Array::new_with_length(0), |handle| handle.get_agent_types())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_state_codes() -> Uint8Array {
+    with_simulation(Uint8Array::new_with_length(0), |handle| handle.get_agent_state_codes())
+}
+
+#[wasm_bindgen]
+pub fn get_traffic_data() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_traffic_data())
+}
+
+#[wasm_bindgen]
+pub fn get_learning_convergence() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_learning_convergence())
+}
+
+#[wasm_bindgen]
+pub fn get_emergency_state() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_emergency_state())
+}
+
+#[wasm_bindgen]
+pub fn get_response_time_stats() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_response_time_stats())
+}
+
+#[wasm_bindgen]
+pub fn start() {
+    with_simulation_mut((), |handle| handle.start())
+}
+
+#[wasm_bindgen]
+pub fn pause() {
+    with_simulation_mut((), |handle| handle.pause())
+}
+
+#[wasm_bindgen]
+pub fn set_speed(multiplier: f32) {
+    with_simulation_mut((), |handle| handle.set_speed(multiplier))
+}
+
+#[wasm_bindgen]
+pub fn is_running() -> bool {
+    with_simulation(false, |handle| handle.is_running())
+}
+
+#[wasm_bindgen]
+pub fn set_time_scale(seconds_per_real_second: f64) {
+    with_simulation_mut((), |handle| handle.set_time_scale(seconds_per_real_second))
+}
+
+#[wasm_bindgen]
+pub fn get_sim_datetime() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_sim_datetime())
+}
+
+#[wasm_bindgen]
+pub fn get_simulation_time() -> f32 {
+    with_simulation(0.0, |handle| handle.get_simulation_time())
+}
+
+#[wasm_bindgen]
+pub fn get_agent_count() -> u32 {
+    with_simulation(0, |handle| handle.get_agent_count())
+}
+
+#[wasm_bindgen]
+pub fn get_seed() -> u64 {
+    with_simulation(0, |handle| handle.get_seed())
+}
+
+#[cfg(feature = "scripting")]
+#[wasm_bindgen]
+pub fn load_plans(plans: &JsValue) -> Result<(), JsValue> {
+    with_simulation_mut(not_initialized(), |handle| handle.load_plans(plans))
+}
+
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+pub fn save_state() -> Result<Vec<u8>, JsValue> {
+    with_simulation(not_initialized(), |handle| handle.save_state())
+}
+
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+pub fn load_state(blob: &[u8]) -> Result<(), JsValue> {
+    install(SimulationHandle::from_state(blob)?);
+    Ok(())
+}
+
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+pub fn start_recording(trajectory_interval_seconds: Option<f64>) -> Result<(), JsValue> {
+    with_simulation_mut(not_initialized(), |handle| handle.start_recording(trajectory_interval_seconds))
+}
+
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+pub fn stop_recording() -> Result<Vec<u8>, JsValue> {
+    with_simulation_mut(not_initialized(), |handle| handle.stop_recording())
+}
+
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+pub fn replay_recording(blob: &[u8], ticks: Option<u32>) -> Result<(), JsValue> {
+    install(SimulationHandle::from_recording(blob, ticks)?);
+    Ok(())
+}
+
+#[cfg(feature = "networking")]
+#[wasm_bindgen]
+pub fn get_recording_trajectory(blob: &[u8]) -> Result<JsValue, JsValue> {
+    let recording = Recording::decode(blob).map_err(js_error)?;
+    Ok(to_value(&recording.trajectory).unwrap_or(JsValue::NULL))
+}
+
+#[cfg(feature = "economics")]
+#[wasm_bindgen]
+pub fn get_ride_hail_state() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_ride_hail_state())
+}
+
+#[cfg(feature = "economics")]
+#[wasm_bindgen]
+pub fn get_ride_hail_metrics() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_ride_hail_metrics())
+}
+
+#[cfg(feature = "economics")]
+#[wasm_bindgen]
+pub fn get_charging_stations() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_charging_stations())
+}
+
+#[cfg(feature = "economics")]
+#[wasm_bindgen]
+pub fn get_charging_demand() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_charging_demand())
+}
+
+#[cfg(feature = "advanced-ai")]
+#[wasm_bindgen]
+pub fn get_autonomous_state() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_autonomous_state())
+}
+
+#[cfg(feature = "physics")]
+#[wasm_bindgen]
+pub fn get_drone_states() -> JsValue {
+    with_simulation(JsValue::NULL, |handle| handle.get_drone_states())
+}
+
+// Export performance system classes directly for JS usage
+#[wasm_bindgen]
+pub struct WasmPerformanceProfile {
+    inner: PerformanceProfile,
+}
+
+impl Default for WasmPerformanceProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmPerformanceProfile {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmPerformanceProfile {
+        WasmPerformanceProfile {
+            inner: PerformanceProfile::default(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn target_fps(&self) -> u32 {
+        self.inner.target_fps()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_agents(&self) -> u32 {
+        self.inner.max_agents()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn render_distance(&self) -> f32 {
+        self.inner.render_distance()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn update_frequency(&self) -> u32 {
+        self.inner.update_frequency()
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmDeviceBenchmark {
+    inner: DeviceBenchmark,
+}
+
+impl Default for WasmDeviceBenchmark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmDeviceBenchmark {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmDeviceBenchmark {
+        WasmDeviceBenchmark {
+            inner: DeviceBenchmark::new(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn run_cpu_test(&self) -> f64 {
+        self.inner.run_cpu_test()
+    }
+
+    #[wasm_bindgen]
+    pub fn test_memory_bandwidth(&self) -> f64 {
+        self.inner.test_memory_bandwidth()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_recommended_profile(&self, cpu_score: f64, memory_mb: f64, refresh_rate: u32) -> WasmPerformanceProfile {
+        let profile = self.inner.get_recommended_profile(cpu_score, memory_mb, refresh_rate);
+        WasmPerformanceProfile { inner: profile }
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmAdaptiveScaler {
+    inner: AdaptiveScaler,
+}
+
+impl Default for WasmAdaptiveScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmAdaptiveScaler {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmAdaptiveScaler {
+        WasmAdaptiveScaler {
+            inner: AdaptiveScaler::new(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_target_fps(&mut self, fps: f64) {
+        self.inner.set_target_fps(fps);
+    }
+
+    #[wasm_bindgen]
+    pub fn update_fps(&mut self, current_fps: f64) {
+        self.inner.update_fps_simple(current_fps);
+    }
+
+    #[wasm_bindgen]
+    pub fn should_adapt(&self) -> bool {
+        self.inner.should_adapt()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_adaptation_direction(&self) -> i32 {
+        self.inner.get_adaptation_direction()
+    }
+}
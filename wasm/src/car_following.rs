@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::agent::{Agent, AgentState, AgentType, Point2D};
+use crate::traffic::{CongestionField, TrafficData};
+use crate::world::Road;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarFollowingParams {
+    pub time_headway: f32, // Seconds kept to the vehicle in front
+    pub min_gap: f32,      // Meters at standstill
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarFollowingModel {
+    pub human: CarFollowingParams,
+    pub autonomous: CarFollowingParams,
+    pub platoon_headway: f32,    // Seconds between AVs inside a highway platoon
+    pub max_platoon_size: usize,
+    pub lookahead: f32,          // Meters searched for a leader
+    pub lane_width: f32,
+}
+
+impl Default for CarFollowingModel {
+    fn default() -> Self {
+        Self {
+            human: CarFollowingParams { time_headway: 1.5, min_gap: 2.0 },
+            autonomous: CarFollowingParams { time_headway: 0.6, min_gap: 1.5 },
+            platoon_headway: 0.3,
+            max_platoon_size: 8,
+            lookahead: 100.0,
+            lane_width: 4.0,
+        }
+    }
+}
+
+struct Vehicle {
+    index: usize,
+    id: u32,
+    position: Point2D,
+    heading: (f32, f32),
+    desired_speed: f32, // m/s
+    autonomous: bool,
+}
+
+impl CarFollowingModel {
+    fn params(&self, autonomous: bool) -> &CarFollowingParams {
+        if autonomous { &self.autonomous } else { &self.human }
+    }
+
+    /// Caps each vehicle's speed to what its gap to the leader allows: v = (gap - min_gap) / headway.
+    /// AVs following AVs on a highway close up into platoons with an even shorter headway.
+    /// Only the agents at `indices` take part.
+    pub fn apply(&self, agents: &mut [Agent], indices: &[usize], highways: &[&Road]) {
+        let vehicles: Vec<Vehicle> = indices.iter()
+            .map(|&index| (index, &agents[index]))
+            .filter(|(_, a)| matches!(a.state, AgentState::Traveling) && CongestionField::is_motorized(&a.agent_type))
+            .map(|(index, a)| {
+                let angle = a.heading();
+                Vehicle {
+                    index,
+                    id: a.id,
+                    position: a.position.clone(),
+                    heading: (angle.cos(), angle.sin()),
+                    desired_speed: a.speed / 3.6,
+                    autonomous: matches!(a.agent_type, AgentType::Autonomous),
+                }
+            })
+            .collect();
+
+        // Bucket by cell so we only compare nearby vehicles
+        let mut grid: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (v, vehicle) in vehicles.iter().enumerate() {
+            grid.entry(self.cell(&vehicle.position)).or_default().push(v);
+        }
+
+        let mut leader_of: Vec<Option<(usize, f32)>> = vec![None; vehicles.len()];
+        for (v, vehicle) in vehicles.iter().enumerate() {
+            let (cx, cy) = self.cell(&vehicle.position);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let Some(bucket) = grid.get(&(cx + dx, cy + dy)) else { continue };
+                    for &other in bucket {
+                        if other == v {
+                            continue;
+                        }
+                        if let Some(gap) = self.gap_to(vehicle, &vehicles[other]) {
+                            if leader_of[v].is_none_or(|(_, best)| gap < best) {
+                                leader_of[v] = Some((other, gap));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for &index in indices {
+            agents[index].platoon_id = None;
+        }
+
+        for (v, vehicle) in vehicles.iter().enumerate() {
+            let Some((leader, gap)) = leader_of[v] else { continue };
+
+            let platoon = vehicle.autonomous
+                && vehicles[leader].autonomous
+                && Self::on_highway(&vehicle.position, highways)
+                && self.platoon_position(v, &leader_of, &vehicles) < self.max_platoon_size;
+
+            let headway = if platoon {
+                self.platoon_headway
+            } else {
+                self.params(vehicle.autonomous).time_headway
+            };
+            let min_gap = self.params(vehicle.autonomous).min_gap;
+
+            // Creep forward rather than stop dead so queues always drain
+            let allowed = ((gap - min_gap).max(0.0) / headway).max(0.5);
+            let factor = (allowed / vehicle.desired_speed.max(0.1)).min(1.0);
+
+            let agent = &mut agents[vehicle.index];
+            agent.speed_factor = agent.speed_factor.min(factor);
+            if platoon {
+                let root = &vehicles[self.platoon_root(v, &leader_of, &vehicles)];
+                agent.platoon_id = Some(root.id);
+                agents[root.index].platoon_id = Some(root.id);
+            }
+        }
+    }
+
+    fn cell(&self, position: &Point2D) -> (i32, i32) {
+        (
+            (position.x / self.lookahead).floor() as i32,
+            (position.y / self.lookahead).floor() as i32,
+        )
+    }
+
+    /// Distance to `leader` if it is ahead of `follower` in the same lane and direction.
+    fn gap_to(&self, follower: &Vehicle, leader: &Vehicle) -> Option<f32> {
+        let dx = leader.position.x - follower.position.x;
+        let dy = leader.position.y - follower.position.y;
+        let along = dx * follower.heading.0 + dy * follower.heading.1;
+        let lateral = (dx * follower.heading.1 - dy * follower.heading.0).abs();
+        let aligned = follower.heading.0 * leader.heading.0 + follower.heading.1 * leader.heading.1;
+
+        if along > 0.0 && along < self.lookahead && lateral < self.lane_width && aligned > 0.7 {
+            Some(along)
+        } else {
+            None
+        }
+    }
+
+    /// How many AVs are in front of `v` in an unbroken chain.
+    fn platoon_position(&self, v: usize, leader_of: &[Option<(usize, f32)>], vehicles: &[Vehicle]) -> usize {
+        let mut position = 0;
+        let mut current = v;
+        while let Some((leader, _)) = leader_of[current] {
+            if !vehicles[leader].autonomous || position >= self.max_platoon_size {
+                break;
+            }
+            position += 1;
+            current = leader;
+        }
+        position
+    }
+
+    fn on_highway(position: &Point2D, highways: &[&Road]) -> bool {
+        highways.iter().any(|road| {
+            road.path.windows(2).any(|segment| {
+                TrafficData::point_to_line_distance(position, &segment[0], &segment[1]) < road.width.max(10.0)
+            })
+        })
+    }
+
+    /// Platoons are named after the agent driving the lead vehicle.
+    fn platoon_root(&self, v: usize, leader_of: &[Option<(usize, f32)>], vehicles: &[Vehicle]) -> usize {
+        let mut current = v;
+        for _ in 0..self.max_platoon_size {
+            match leader_of[current] {
+                Some((leader, _)) if vehicles[leader].autonomous => current = leader,
+                _ => break,
+            }
+        }
+        current
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use crate::agent::{Agent, AgentState, AgentType, Point2D};
+use crate::clock::{self, SimClock};
+use crate::world::POI;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargingConfig {
+    pub ev_share: f32,                 // Share of car owners driving an EV
+    pub battery_kwh: f32,
+    pub consumption_kwh_per_km: f32,
+    pub charger_kw: f32,
+    pub default_plugs: u32,            // Used when a station POI has no capacity set
+    pub range_anxiety_soc: f32,        // Detour to charge if a trip would end below this
+    pub target_soc: f32,               // Unplug once charged to this level
+    pub sample_interval_hours: f32,
+    pub history_intervals: u32,        // Samples kept per station, older ones are dropped
+}
+
+impl Default for ChargingConfig {
+    fn default() -> Self {
+        Self {
+            ev_share: 0.3,
+            battery_kwh: 60.0,
+            consumption_kwh_per_km: 0.18,
+            charger_kw: 50.0,
+            default_plugs: 4,
+            range_anxiety_soc: 0.2,
+            target_soc: 0.9,
+            sample_interval_hours: 0.25,
+            history_intervals: 672,        // A week at the default interval
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargingStation {
+    pub poi_id: String,
+    pub position: Point2D,
+    pub plugs: u32,
+    pub plugged: Vec<u32>,
+    pub queue: VecDeque<u32>,
+    interval_energy_kwh: f32,
+    interval_peak_queue: u32,
+}
+
+/// One point of the per-station demand time series the optimizer consumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargingSample {
+    pub day: u32,
+    pub time: f32,           // Hour of day
+    pub station_id: String,
+    pub position: Point2D,
+    pub plugs: u32,
+    pub plugged: u32,
+    pub queued: u32,
+    pub peak_queue: u32,
+    pub energy_kwh: f32,     // Delivered during the interval
+    pub demand_kw: f32,      // Average power over the interval
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChargingNetwork {
+    pub config: ChargingConfig,
+    pub stations: Vec<ChargingStation>,
+    pub demand_series: VecDeque<ChargingSample>,
+    pub detours: u32,
+    sample_timer: f32, // Seconds since the last sample
+}
+
+impl ChargingNetwork {
+    pub fn uses_battery(agent: &Agent) -> bool {
+        agent.is_ev && matches!(agent.agent_type, AgentType::Car | AgentType::Autonomous)
+    }
+
+    /// Every CHARGING_STATION POI becomes a station, with its capacity as the plug count.
+    pub fn install(&mut self, pois: &[POI]) {
+        self.stations = pois.iter()
+            .filter(|poi| poi.poi_type == 8) // CHARGING_STATION
+            .map(|poi| ChargingStation {
+                poi_id: poi.id.clone(),
+                position: poi.position.clone(),
+                plugs: if poi.capacity > 0 { poi.capacity } else { self.config.default_plugs },
+                plugged: Vec::new(),
+                queue: VecDeque::new(),
+                interval_energy_kwh: 0.0,
+                interval_peak_queue: 0,
+            })
+            .collect();
+        self.demand_series.clear();
+        self.sample_timer = 0.0;
+    }
+
+    pub fn is_station(&self, poi_id: &str) -> bool {
+        self.stations.iter().any(|s| s.poi_id == poi_id)
+    }
+
+    pub fn nearest_station(&self, position: &Point2D) -> Option<&ChargingStation> {
+        self.stations.iter().min_by(|a, b| {
+            let da = a.position.distance_to(position);
+            let db = b.position.distance_to(position);
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+
+    /// State of charge a trip of `distance` meters would use up.
+    pub fn trip_soc(&self, distance: f32) -> f32 {
+        distance / 1000.0 * self.config.consumption_kwh_per_km / self.config.battery_kwh
+    }
+
+    pub fn drain(&self, agent: &mut Agent, distance: f32) {
+        agent.battery_soc = (agent.battery_soc - self.trip_soc(distance)).max(0.0);
+    }
+
+    /// Takes a plug if one is free, otherwise joins the queue.
+    pub fn arrive(&mut self, agent: &mut Agent, poi_id: &str) {
+        let Some(station) = self.stations.iter_mut().find(|s| s.poi_id == poi_id) else { return };
+
+        if (station.plugged.len() as u32) < station.plugs {
+            station.plugged.push(agent.id);
+        } else {
+            station.queue.push_back(agent.id);
+            station.interval_peak_queue = station.interval_peak_queue.max(station.queue.len() as u32);
+        }
+        agent.state = AgentState::Charging;
+    }
+
+    pub fn update(&mut self, dt: f32, clock: &SimClock, agents: &mut [Agent], agent_lookup: &BTreeMap<u32, usize>) {
+        let energy = self.config.charger_kw * clock::hours(dt);
+
+        for station in &mut self.stations {
+            let mut done = Vec::new();
+            for &agent_id in &station.plugged {
+                let Some(agent) = agent_lookup.get(&agent_id).map(|&index| &mut agents[index]) else {
+                    done.push(agent_id);
+                    continue;
+                };
+
+                let needed = (self.config.target_soc - agent.battery_soc).max(0.0) * self.config.battery_kwh;
+                let delivered = energy.min(needed);
+                agent.battery_soc += delivered / self.config.battery_kwh;
+                station.interval_energy_kwh += delivered;
+
+                if agent.battery_soc >= self.config.target_soc - f32::EPSILON {
+                    agent.state = AgentState::AtDestination;
+                    done.push(agent_id);
+                }
+            }
+
+            station.plugged.retain(|id| !done.contains(id));
+            while (station.plugged.len() as u32) < station.plugs {
+                let Some(next) = station.queue.pop_front() else { break };
+                station.plugged.push(next);
+            }
+        }
+
+        self.sample_timer += dt;
+        if self.sample_timer >= self.config.sample_interval_hours * clock::SECONDS_PER_HOUR {
+            self.record_sample(clock);
+        }
+    }
+
+    /// Adds one sample per station and drops the oldest beyond `history_intervals`, so the
+    /// series stays the same size however long the run.
+    fn record_sample(&mut self, clock: &SimClock) {
+        let interval = clock::hours(self.sample_timer);
+        let (day, time) = (clock.day(), clock.hour_of_day());
+        self.sample_timer = 0.0;
+
+        for station in &mut self.stations {
+            self.demand_series.push_back(ChargingSample {
+                day,
+                time,
+                station_id: station.poi_id.clone(),
+                position: station.position.clone(),
+                plugs: station.plugs,
+                plugged: station.plugged.len() as u32,
+                queued: station.queue.len() as u32,
+                peak_queue: station.interval_peak_queue,
+                energy_kwh: station.interval_energy_kwh,
+                demand_kw: station.interval_energy_kwh / interval,
+            });
+            station.interval_energy_kwh = 0.0;
+            station.interval_peak_queue = station.queue.len() as u32;
+        }
+
+        let capacity = self.config.history_intervals as usize * self.stations.len();
+        while self.demand_series.len() > capacity {
+            self.demand_series.pop_front();
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+pub const SECONDS_PER_HOUR: f32 = 3600.0;
+pub const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Simulation seconds as hours, the unit schedules, rates and energy figures are given in.
+pub fn hours(seconds: f32) -> f32 {
+    seconds / SECONDS_PER_HOUR
+}
+
+/// Meters covered in `seconds` at `speed_kmh`.
+pub fn distance_m(speed_kmh: f32, seconds: f32) -> f32 {
+    speed_kmh / 3.6 * seconds
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarDate {
+    pub year: i32,
+    pub month: u32, // 1-12
+    pub day: u32,   // 1-31
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimDateTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub weekday: u32,     // 0 = Monday
+    pub day_index: u32,   // Days since the simulation started
+    pub elapsed_seconds: f64,
+}
+
+/// The one source of simulated time. Counts seconds since midnight of `start_date`;
+/// everything else (hour of day, day counter, calendar date) is derived from that.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimClock {
+    pub elapsed_seconds: f64,
+    pub seconds_per_real_second: f64, // 3600 = one simulated hour per real second
+    pub start_date: CalendarDate,
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self {
+            elapsed_seconds: 0.0,
+            seconds_per_real_second: 3600.0,
+            start_date: CalendarDate { year: 2024, month: 1, day: 1 },
+        }
+    }
+}
+
+impl SimClock {
+    /// Simulated seconds that pass during `real_seconds` of wall time.
+    pub fn scale(&self, real_seconds: f64) -> f32 {
+        (real_seconds * self.seconds_per_real_second) as f32
+    }
+
+    pub fn advance(&mut self, sim_seconds: f32) {
+        self.elapsed_seconds += sim_seconds as f64;
+    }
+
+    pub fn day(&self) -> u32 {
+        (self.elapsed_seconds / SECONDS_PER_DAY).floor() as u32
+    }
+
+    pub fn seconds_of_day(&self) -> f64 {
+        self.elapsed_seconds.rem_euclid(SECONDS_PER_DAY)
+    }
+
+    pub fn hour_of_day(&self) -> f32 {
+        (self.seconds_of_day() / SECONDS_PER_HOUR as f64) as f32
+    }
+
+    /// Clock seconds at the midnight that started the current day.
+    pub fn midnight(&self) -> f64 {
+        self.elapsed_seconds - self.seconds_of_day()
+    }
+
+    /// Clock seconds at `hour` hours past today's midnight. Hours of 24 and up fall on the
+    /// next day, so a late departure or a plan time like 25:30 stays ahead of the clock.
+    pub fn at_hour(&self, hour: f32) -> f64 {
+        self.midnight() + hour as f64 * SECONDS_PER_HOUR as f64
+    }
+
+    pub fn date(&self) -> CalendarDate {
+        civil_from_days(days_from_civil(self.start_date) + self.day() as i64)
+    }
+
+    pub fn datetime(&self) -> SimDateTime {
+        let date = self.date();
+        let second_of_day = self.seconds_of_day() as u32;
+        let days = days_from_civil(date);
+        SimDateTime {
+            year: date.year,
+            month: date.month,
+            day: date.day,
+            hour: second_of_day / 3600,
+            minute: second_of_day / 60 % 60,
+            second: second_of_day % 60,
+            weekday: (days + 3).rem_euclid(7) as u32, // 1970-01-01 was a Thursday
+            day_index: self.day(),
+            elapsed_seconds: self.elapsed_seconds,
+        }
+    }
+}
+
+// Proleptic Gregorian conversions after Howard Hinnant's `days_from_civil`/`civil_from_days`
+
+fn days_from_civil(date: CalendarDate) -> i64 {
+    let year = date.year as i64 - if date.month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = date.month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + date.day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> CalendarDate {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    CalendarDate { year: year as i32, month, day }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::agent::{Agent, Point2D};
+use crate::agent_store::AgentStore;
+use crate::performance::PerformanceProfile;
+
+const INDEX_CELL_SIZE: f32 = 100.0;  // Meters
+const CLUSTER_CELL_SIZE: f32 = 50.0; // Meters at LOD 1, doubling with every level after
+
+/// The visible part of the map: a ground-plane box, or the point under the camera.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum View {
+    Box { min_x: f32, min_y: f32, max_x: f32, max_y: f32 },
+    Camera { x: f32, y: f32 },
+}
+
+/// Uniform grid of agent indices, rebuilt only when time has moved or the population changed.
+/// Anything that moves agents without a step has to `invalidate` it.
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    cells: HashMap<(i32, i32), Vec<u32>>,
+    built_for: Option<(f64, usize)>, // Clock seconds, agent count
+}
+
+impl SpatialIndex {
+    fn cell(x: f32, y: f32) -> (i32, i32) {
+        ((x / INDEX_CELL_SIZE).floor() as i32, (y / INDEX_CELL_SIZE).floor() as i32)
+    }
+
+    /// Forces the next `update` to rebuild.
+    pub fn invalidate(&mut self) {
+        self.built_for = None;
+    }
+
+    pub fn update(&mut self, agents: &[Agent], time: f64) {
+        if self.built_for == Some((time, agents.len())) {
+            return;
+        }
+        self.built_for = Some((time, agents.len()));
+        self.cells.values_mut().for_each(Vec::clear);
+        for (index, agent) in agents.iter().enumerate() {
+            self.cells.entry(Self::cell(agent.position.x, agent.position.y)).or_default().push(index as u32);
+        }
+    }
+
+    /// Indices of agents in cells touching the box, in ascending order.
+    pub fn candidates(&self, min: &Point2D, max: &Point2D) -> Vec<u32> {
+        let (min_cell, max_cell) = (Self::cell(min.x, min.y), Self::cell(max.x, max.y));
+        let span = (max_cell.0 - min_cell.0 + 1) as i64 * (max_cell.1 - min_cell.1 + 1) as i64;
+
+        let mut found: Vec<u32> = if span > self.cells.len() as i64 {
+            // Box larger than the populated area: walk the occupied cells instead
+            self.cells.iter()
+                .filter(|((cx, cy), _)| (min_cell.0..=max_cell.0).contains(cx) && (min_cell.1..=max_cell.1).contains(cy))
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect()
+        } else {
+            (min_cell.0..=max_cell.0)
+                .flat_map(|cx| (min_cell.1..=max_cell.1).map(move |cy| (cx, cy)))
+                .filter_map(|cell| self.cells.get(&cell))
+                .flat_map(|indices| indices.iter().copied())
+                .collect()
+        };
+        found.sort_unstable();
+        found
+    }
+}
+
+/// Agents to draw. Those at LOD 0 come individually in the usual compact layout; the rest
+/// are merged into clusters on a grid that gets coarser with every level.
+#[derive(Debug, Clone, Default)]
+pub struct AgentsInView {
+    pub ids: Vec<u32>,
+    pub agents: AgentStore,
+    pub cluster_positions: Vec<f32>, // Centroid x, y per cluster
+    pub cluster_counts: Vec<u32>,
+    pub cluster_levels: Vec<u8>,
+}
+
+/// Culls to the view (within the profile's render distance when culling is on) and picks a
+/// level of detail per agent: `lod` for a box, and for a camera whichever is coarser of `lod`
+/// and the agent's distance band, the radius being split into `lod_levels` equal bands.
+pub fn agents_in_view(
+    index: &SpatialIndex,
+    agents: &[Agent],
+    view: &View,
+    radius: Option<f32>,
+    lod: u32,
+    profile: &PerformanceProfile,
+) -> AgentsInView {
+    let coarsest = profile.lod_levels().max(1) - 1;
+    let lod = lod.min(coarsest);
+
+    let (min, max, camera) = match *view {
+        View::Box { min_x, min_y, max_x, max_y } => {
+            (Point2D::new(min_x.min(max_x), min_y.min(max_y)), Point2D::new(min_x.max(max_x), min_y.max(max_y)), None)
+        }
+        View::Camera { x, y } => {
+            let mut radius = radius.unwrap_or(profile.render_distance());
+            if profile.culling_enabled() {
+                radius = radius.min(profile.render_distance());
+            }
+            let radius = radius.max(1.0);
+            (Point2D::new(x - radius, y - radius), Point2D::new(x + radius, y + radius), Some((Point2D::new(x, y), radius)))
+        }
+    };
+
+    let visible: Vec<u32> = if profile.culling_enabled() {
+        index.candidates(&min, &max)
+    } else {
+        (0..agents.len() as u32).collect()
+    };
+
+    let mut result = AgentsInView::default();
+    let mut clusters: BTreeMap<(u32, i32, i32), (f32, f32, u32)> = BTreeMap::new();
+
+    for i in visible {
+        let agent = &agents[i as usize];
+        let position = &agent.position;
+        let level = match &camera {
+            Some((center, radius)) => {
+                let distance = center.distance_to(position);
+                if profile.culling_enabled() && distance > *radius {
+                    continue;
+                }
+                let band = (distance / (radius / (coarsest + 1) as f32)) as u32;
+                band.clamp(lod, coarsest)
+            }
+            None => {
+                let inside = (min.x..=max.x).contains(&position.x) && (min.y..=max.y).contains(&position.y);
+                if profile.culling_enabled() && !inside {
+                    continue;
+                }
+                lod
+            }
+        };
+
+        if level == 0 {
+            result.ids.push(agent.id);
+            result.agents.push(agent);
+        } else {
+            let size = CLUSTER_CELL_SIZE * (1u32 << (level - 1).min(16)) as f32;
+            let key = (level, (position.x / size).floor() as i32, (position.y / size).floor() as i32);
+            let cluster = clusters.entry(key).or_insert((0.0, 0.0, 0));
+            cluster.0 += position.x;
+            cluster.1 += position.y;
+            cluster.2 += 1;
+        }
+    }
+
+    for ((level, _, _), (sum_x, sum_y, count)) in clusters {
+        result.cluster_positions.extend([sum_x / count as f32, sum_y / count as f32]);
+        result.cluster_counts.push(count);
+        result.cluster_levels.push(level as u8);
+    }
+    result
+}
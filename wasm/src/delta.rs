@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use crate::agent::Agent;
+use crate::agent_store::AgentStore;
+
+/// How many frames of spawn/despawn history are kept. Clients further behind get a full resync.
+const POPULATION_HISTORY_FRAMES: u32 = 4096;
+
+/// Agents that changed since a client's last frame. Apply `despawned`, then upsert every
+/// agent in `ids`/`agents`; `spawned` lists which of those are new. With `full` set the
+/// client missed too much and should drop everything it has first.
+#[derive(Debug, Clone, Default)]
+pub struct AgentDelta {
+    pub frame: u32, // Pass back as `since` next time
+    pub full: bool,
+    pub ids: Vec<u32>,
+    pub agents: AgentStore, // Same layout as the full buffers, one entry per id
+    pub spawned: Vec<u32>,
+    pub despawned: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Seen {
+    x: f32,
+    y: f32,
+    state: u8,
+    agent_type: u8,
+    changed_at: u32,
+}
+
+impl Seen {
+    fn of(agent: &Agent, frame: u32) -> Self {
+        Self {
+            x: agent.position.x,
+            y: agent.position.y,
+            state: agent.state.code(),
+            agent_type: agent.agent_type.code(),
+            changed_at: frame,
+        }
+    }
+
+    fn looks_like(&self, other: &Seen) -> bool {
+        (self.x, self.y, self.state, self.agent_type) == (other.x, other.y, other.state, other.agent_type)
+    }
+}
+
+/// Remembers what each agent looked like when last observed and the frame it last changed.
+/// Each `observe` is one frame and only looks at the agents the world marked as changed, so
+/// agents dwelling at an activity cost nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeTracker {
+    frame: u32,
+    seen: BTreeMap<u32, Seen>, // By agent id
+    by_change: BTreeSet<(u32, u32)>, // (changed_at, id) of every seen agent
+    spawned: VecDeque<(u32, u32)>, // (frame, id)
+    despawned: VecDeque<(u32, u32)>,
+    horizon: u32, // Oldest frame the population history still covers
+}
+
+impl ChangeTracker {
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Compares the agents with these ids to how they last looked. Ids missing from `lookup`
+    /// are agents that are gone.
+    pub fn observe(&mut self, ids: &BTreeSet<u32>, agents: &[Agent], lookup: &BTreeMap<u32, usize>) {
+        self.frame += 1;
+        let frame = self.frame;
+
+        for &id in ids {
+            let Some(&index) = lookup.get(&id) else {
+                if let Some(seen) = self.seen.remove(&id) {
+                    self.by_change.remove(&(seen.changed_at, id));
+                    self.despawned.push_back((frame, id));
+                }
+                continue;
+            };
+            let current = Seen::of(&agents[index], frame);
+            match self.seen.get_mut(&id) {
+                Some(seen) => {
+                    if !seen.looks_like(&current) {
+                        self.by_change.remove(&(seen.changed_at, id));
+                        self.by_change.insert((frame, id));
+                        *seen = current;
+                    }
+                }
+                None => {
+                    self.seen.insert(id, current);
+                    self.by_change.insert((frame, id));
+                    self.spawned.push_back((frame, id));
+                }
+            }
+        }
+
+        self.horizon = self.horizon.max(frame.saturating_sub(POPULATION_HISTORY_FRAMES));
+        let horizon = self.horizon;
+        while self.spawned.front().is_some_and(|(at, _)| *at <= horizon) {
+            self.spawned.pop_front();
+        }
+        while self.despawned.front().is_some_and(|(at, _)| *at <= horizon) {
+            self.despawned.pop_front();
+        }
+    }
+
+    /// Everything that changed after frame `since`, or everyone when `since` is unknown to
+    /// this tracker (0, from the future, or older than the history).
+    pub fn changes_since(&self, since: u32, agents: &[Agent], lookup: &BTreeMap<u32, usize>) -> AgentDelta {
+        let full = since == 0 || since > self.frame || since < self.horizon;
+        let mut delta = AgentDelta { frame: self.frame, full, ..AgentDelta::default() };
+
+        if full {
+            for agent in agents {
+                delta.ids.push(agent.id);
+                delta.agents.push(agent);
+            }
+            return delta;
+        }
+
+        for &(_, id) in self.by_change.range((since + 1, 0)..) {
+            if let Some(&index) = lookup.get(&id) {
+                delta.ids.push(id);
+                delta.agents.push(&agents[index]);
+            }
+        }
+        delta.spawned = self.spawned.iter()
+            .filter(|(at, id)| *at > since && self.seen.contains_key(id))
+            .map(|(_, id)| *id)
+            .collect();
+        delta.despawned = self.despawned.iter()
+            .filter(|(at, id)| *at > since && !self.seen.contains_key(id))
+            .map(|(_, id)| *id)
+            .collect();
+        delta
+    }
+}
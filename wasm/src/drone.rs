@@ -0,0 +1,429 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use rand::Rng;
+use crate::agent::Point2D;
+use crate::clock;
+use crate::traffic::TrafficData;
+use crate::world::{Building, POI};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Point3D {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Point3D {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    fn at(point: &Point2D, z: f32) -> Self {
+        Self::new(point.x, point.y, z)
+    }
+
+    fn distance_to(&self, other: &Point3D) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2)).sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroneConfig {
+    pub drones_per_depot: u32,
+    pub mission_rate: f32,            // Delivery requests per hour across the city
+    pub cruise_speed_kmh: f32,
+    pub climb_speed_kmh: f32,
+    pub corridor_altitudes: Vec<f32>, // Meters, one layer per heading sector
+    pub building_clearance: f32,      // Meters kept above and around buildings
+    pub drop_altitude: f32,
+    pub drop_hours: f32,
+    pub battery_wh: f32,
+    pub cruise_wh_per_km: f32,
+    pub climb_wh_per_m: f32,
+    pub reserve_soc: f32,
+    pub charge_rate_w: f32,
+}
+
+impl Default for DroneConfig {
+    fn default() -> Self {
+        Self {
+            drones_per_depot: 2,
+            mission_rate: 6.0,
+            cruise_speed_kmh: 50.0,
+            climb_speed_kmh: 18.0,
+            corridor_altitudes: vec![60.0, 75.0, 90.0, 105.0],
+            building_clearance: 20.0,
+            drop_altitude: 5.0,
+            drop_hours: 0.02,
+            battery_wh: 400.0,
+            cruise_wh_per_km: 30.0,
+            climb_wh_per_m: 0.15,
+            reserve_soc: 0.2,
+            charge_rate_w: 200.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DroneStatus {
+    Idle,
+    Outbound,
+    Delivering,
+    Returning,
+    Emergency,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryMission {
+    pub origin_id: String,
+    pub destination_id: String,
+    pub requested_at: f64, // Clock seconds
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Drone {
+    pub id: u32,
+    pub depot_id: String,
+    pub depot: Point2D,
+    pub position: Point3D,
+    pub status: DroneStatus,
+    pub battery_wh: f32,
+    pub mission: Option<DeliveryMission>,
+    pub flight_path: Vec<Point3D>,
+    pub busy_until: f64, // Clock seconds
+    return_path: Vec<Point3D>,
+}
+
+/// What the frontend layer draws for each drone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroneState {
+    pub id: u32,
+    pub drone_type: String,
+    pub status: DroneStatus,
+    pub position: Point3D,
+    pub heading: f32,
+    pub battery_soc: f32,
+    pub path: Vec<Point3D>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DroneMetrics {
+    pub requested: u32,
+    pub delivered: u32,
+    pub rejected: u32,   // No drone with enough battery for the round trip
+    pub emergencies: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DroneFleet {
+    pub config: DroneConfig,
+    pub drones: Vec<Drone>,
+    pub metrics: DroneMetrics,
+    obstacles: Vec<Obstacle>,
+}
+
+/// Building footprint reduced to an inflated bounding box and a roof height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Obstacle {
+    min: Point2D,
+    max: Point2D,
+    height: f32,
+}
+
+impl Obstacle {
+    fn from_building(building: &Building, clearance: f32) -> Option<Self> {
+        let first = building.footprint.first()?;
+        let mut min = first.clone();
+        let mut max = first.clone();
+        for p in &building.footprint {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+        min.x -= clearance;
+        min.y -= clearance;
+        max.x += clearance;
+        max.y += clearance;
+        Some(Self { min, max, height: building.height + clearance })
+    }
+
+    fn center(&self) -> Point2D {
+        Point2D::new((self.min.x + self.max.x) / 2.0, (self.min.y + self.max.y) / 2.0)
+    }
+
+    fn contains(&self, p: &Point2D) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+    }
+
+    /// Whether the segment passes through the box, checked by sampling along it.
+    fn blocks(&self, a: &Point2D, b: &Point2D) -> bool {
+        let radius = self.center().distance_to(&self.max);
+        if TrafficData::point_to_line_distance(&self.center(), a, b) > radius {
+            return false;
+        }
+        let steps = (a.distance_to(b) / 5.0).ceil().max(1.0) as usize;
+        (0..=steps).any(|i| {
+            let t = i as f32 / steps as f32;
+            self.contains(&Point2D::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t))
+        })
+    }
+}
+
+impl DroneFleet {
+    /// Bases drones at SHOP POIs and WAREHOUSE buildings.
+    pub fn deploy(&mut self, pois: &[POI], buildings: &[Building]) {
+        self.drones.clear();
+        self.obstacles = buildings.iter()
+            .filter_map(|b| Obstacle::from_building(b, self.config.building_clearance))
+            .collect();
+
+        let mut depots: Vec<(String, Point2D)> = pois.iter()
+            .filter(|poi| poi.poi_type == 2) // SHOP
+            .map(|poi| (poi.id.clone(), poi.position.clone()))
+            .collect();
+        for building in buildings.iter().filter(|b| b.building_type == 4) { // WAREHOUSE
+            if let Some(obstacle) = Obstacle::from_building(building, 0.0) {
+                depots.push((building.id.clone(), obstacle.center()));
+            }
+        }
+
+        for (depot_id, depot) in depots {
+            for _ in 0..self.config.drones_per_depot {
+                self.drones.push(Drone {
+                    id: self.drones.len() as u32,
+                    depot_id: depot_id.clone(),
+                    position: Point3D::at(&depot, 0.0),
+                    depot: depot.clone(),
+                    status: DroneStatus::Idle,
+                    battery_wh: self.config.battery_wh,
+                    mission: None,
+                    flight_path: Vec::new(),
+                    busy_until: 0.0,
+                    return_path: Vec::new(),
+                });
+            }
+        }
+    }
+
+    /// Advances the fleet by `dt` simulated seconds to `now`, in clock seconds.
+    pub fn update(&mut self, dt: f32, now: f64, pois: &[POI], rng: &mut impl Rng) {
+        if self.drones.is_empty() {
+            return;
+        }
+
+        let homes: Vec<&POI> = pois.iter().filter(|poi| poi.poi_type == 0).collect(); // HOME
+        if !homes.is_empty() && rng.gen::<f32>() < self.config.mission_rate * clock::hours(dt) {
+            let home = homes[rng.gen_range(0..homes.len())];
+            self.assign_mission(home, now, rng);
+        }
+
+        for i in 0..self.drones.len() {
+            self.update_drone(i, dt, now);
+        }
+    }
+
+    /// Gives the delivery to a random depot's idle drone that can make the round trip.
+    fn assign_mission(&mut self, home: &POI, now: f64, rng: &mut impl Rng) {
+        self.metrics.requested += 1;
+
+        let mut candidates: Vec<usize> = (0..self.drones.len())
+            .filter(|&i| self.drones[i].status == DroneStatus::Idle)
+            .collect();
+        while !candidates.is_empty() {
+            let i = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+            let depot = self.drones[i].depot.clone();
+            let outbound = self.plan_flight(&depot, &home.position);
+            let inbound = self.plan_flight(&home.position, &depot);
+            let needed = self.flight_energy(&outbound) + self.flight_energy(&inbound);
+            let drone = &mut self.drones[i];
+            if drone.battery_wh - needed < self.config.battery_wh * self.config.reserve_soc {
+                continue;
+            }
+
+            drone.mission = Some(DeliveryMission {
+                origin_id: drone.depot_id.clone(),
+                destination_id: home.id.clone(),
+                requested_at: now,
+            });
+            drone.flight_path = outbound;
+            drone.return_path = inbound;
+            drone.status = DroneStatus::Outbound;
+            return;
+        }
+
+        self.metrics.rejected += 1;
+    }
+
+    /// Climb to the corridor for this heading (raised until it clears every building under
+    /// the route), fly around anything still too tall, then descend to drop altitude.
+    pub fn plan_flight(&self, from: &Point2D, to: &Point2D) -> Vec<Point3D> {
+        let heading = (to.y - from.y).atan2(to.x - from.x).rem_euclid(2.0 * PI);
+        let layers = &self.config.corridor_altitudes;
+        let sector = ((heading / (2.0 * PI)) * layers.len() as f32) as usize % layers.len().max(1);
+        let mut altitude = layers.get(sector).copied().unwrap_or(60.0);
+
+        let tallest = self.obstacles.iter()
+            .filter(|o| o.blocks(from, to))
+            .map(|o| o.height)
+            .fold(0.0, f32::max);
+        if tallest > altitude {
+            // Next layer up that clears, or the top layer if none does
+            let clearing = layers.iter().copied().filter(|&a| a >= tallest).fold(f32::INFINITY, f32::min);
+            let top = layers.iter().copied().fold(altitude, f32::max);
+            altitude = if clearing.is_finite() { clearing } else { top };
+        }
+
+        let waypoints = self.route_around(from, to, altitude);
+        let mut path = vec![Point3D::at(from, self.config.drop_altitude)];
+        path.extend(waypoints.iter().map(|p| Point3D::at(p, altitude)));
+        path.push(Point3D::at(to, self.config.drop_altitude));
+        path
+    }
+
+    /// Horizontal waypoints from `from` to `to` that skirt obstacles taller than `altitude`.
+    fn route_around(&self, from: &Point2D, to: &Point2D, altitude: f32) -> Vec<Point2D> {
+        let mut route = vec![from.clone()];
+        let mut current = from.clone();
+
+        for _ in 0..8 {
+            let blocking = self.obstacles.iter()
+                .filter(|o| o.height > altitude && !o.contains(&current) && !o.contains(to) && o.blocks(&current, to))
+                .min_by(|a, b| {
+                    let da = a.center().distance_to(&current);
+                    let db = b.center().distance_to(&current);
+                    da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+                });
+            let Some(obstacle) = blocking else { break };
+
+            // Go via the corner that keeps the total distance shortest, just outside the box
+            let (min, max) = (&obstacle.min, &obstacle.max);
+            let corners = [
+                Point2D::new(min.x - 1.0, min.y - 1.0),
+                Point2D::new(max.x + 1.0, min.y - 1.0),
+                Point2D::new(max.x + 1.0, max.y + 1.0),
+                Point2D::new(min.x - 1.0, max.y + 1.0),
+            ];
+            // Corners that also see the target past this obstacle come first
+            let Some(corner) = corners.into_iter()
+                .filter(|c| c.distance_to(&current) > 1.0 && !obstacle.blocks(&current, c))
+                .min_by(|a, b| {
+                    let score = |c: &Point2D| {
+                        let detour = current.distance_to(c) + c.distance_to(to);
+                        if obstacle.blocks(c, to) { detour + 1.0e6 } else { detour }
+                    };
+                    score(a).partial_cmp(&score(b)).unwrap_or(std::cmp::Ordering::Equal)
+                })
+            else {
+                break;
+            };
+
+            route.push(corner.clone());
+            current = corner;
+        }
+
+        route.push(to.clone());
+        route
+    }
+
+    fn flight_energy(&self, path: &[Point3D]) -> f32 {
+        path.windows(2)
+            .map(|w| {
+                let horizontal = ((w[1].x - w[0].x).powi(2) + (w[1].y - w[0].y).powi(2)).sqrt();
+                let climb = (w[1].z - w[0].z).max(0.0);
+                horizontal / 1000.0 * self.config.cruise_wh_per_km + climb * self.config.climb_wh_per_m
+            })
+            .sum()
+    }
+
+    fn update_drone(&mut self, i: usize, dt: f32, now: f64) {
+        let config = &self.config;
+        let drone = &mut self.drones[i];
+
+        match drone.status {
+            DroneStatus::Idle => {
+                drone.battery_wh = (drone.battery_wh + config.charge_rate_w * clock::hours(dt)).min(config.battery_wh);
+            }
+            DroneStatus::Delivering => {
+                if now >= drone.busy_until {
+                    drone.flight_path = std::mem::take(&mut drone.return_path);
+                    drone.status = DroneStatus::Returning;
+                }
+            }
+            DroneStatus::Outbound | DroneStatus::Returning => {
+                let before = drone.position.clone();
+                Self::fly(drone, config, dt);
+                let after = drone.position.clone();
+                let used = self.flight_energy(&[before, after]);
+                let drone = &mut self.drones[i];
+                drone.battery_wh -= used;
+
+                if drone.battery_wh <= 0.0 {
+                    drone.battery_wh = 0.0;
+                    drone.status = DroneStatus::Emergency;
+                    self.metrics.emergencies += 1;
+                    return;
+                }
+
+                if drone.flight_path.len() <= 1 {
+                    drone.flight_path.clear();
+                    if drone.status == DroneStatus::Outbound {
+                        drone.status = DroneStatus::Delivering;
+                        drone.busy_until = now + (self.config.drop_hours * clock::SECONDS_PER_HOUR) as f64;
+                        self.metrics.delivered += 1;
+                    } else {
+                        drone.status = DroneStatus::Idle;
+                        drone.mission = None;
+                        drone.position.z = 0.0;
+                    }
+                }
+            }
+            DroneStatus::Emergency => {}
+        }
+    }
+
+    /// Follows the 3D flight path, climbing and descending slower than cruising.
+    fn fly(drone: &mut Drone, config: &DroneConfig, dt: f32) {
+        let mut time_left = clock::hours(dt);
+
+        while drone.flight_path.len() > 1 && time_left > 0.0 {
+            let target = drone.flight_path[1].clone();
+            let vertical = (target.z - drone.position.z).abs();
+            let horizontal = ((target.x - drone.position.x).powi(2) + (target.y - drone.position.y).powi(2)).sqrt();
+            let speed_kmh = if vertical > horizontal { config.climb_speed_kmh } else { config.cruise_speed_kmh };
+            let reach = speed_kmh * 1000.0 * time_left;
+            let remaining = drone.position.distance_to(&target);
+
+            if reach >= remaining {
+                time_left -= remaining / (speed_kmh * 1000.0);
+                drone.position = target;
+                drone.flight_path.remove(0);
+            } else {
+                let t = reach / remaining;
+                drone.position.x += (target.x - drone.position.x) * t;
+                drone.position.y += (target.y - drone.position.y) * t;
+                drone.position.z += (target.z - drone.position.z) * t;
+                time_left = 0.0;
+            }
+        }
+    }
+
+    pub fn states(&self) -> Vec<DroneState> {
+        self.drones.iter()
+            .map(|drone| {
+                let heading = drone.flight_path.get(1)
+                    .map(|next| (next.y - drone.position.y).atan2(next.x - drone.position.x))
+                    .unwrap_or(0.0);
+                DroneState {
+                    id: drone.id,
+                    drone_type: "delivery".to_string(),
+                    status: drone.status,
+                    position: drone.position.clone(),
+                    heading,
+                    battery_soc: drone.battery_wh / self.config.battery_wh,
+                    path: drone.flight_path.clone(),
+                }
+            })
+            .collect()
+    }
+}
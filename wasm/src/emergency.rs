@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use rand::Rng;
+use crate::agent::{Agent, AgentState, Point2D};
+use crate::clock;
+use crate::pathfinding::{advance_along_path, PathFinder};
+use crate::traffic::CongestionField;
+use crate::world::POI;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyConfig {
+    pub incident_rate: f32,            // Incidents per hour across the city
+    pub ambulances_per_hospital: u32,
+    pub ambulance_speed_kmh: f32,
+    pub on_scene_hours: f32,
+    pub yield_radius: f32,             // Meters around a responding ambulance
+    pub yield_factor: f32,             // Speed multiplier for vehicles pulling over
+}
+
+impl Default for EmergencyConfig {
+    fn default() -> Self {
+        Self {
+            incident_rate: 3.0,
+            ambulances_per_hospital: 2,
+            ambulance_speed_kmh: 60.0,
+            on_scene_hours: 0.33,
+            yield_radius: 60.0,
+            yield_factor: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: u32,
+    pub position: Point2D,
+    pub zone_id: String,
+    pub reported_at: f64, // Clock seconds
+    pub assigned_to: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AmbulanceState {
+    Idle,
+    Responding,
+    OnScene,
+    Returning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ambulance {
+    pub id: u32,
+    pub hospital_id: String,
+    pub home: Point2D,
+    pub position: Point2D,
+    pub state: AmbulanceState,
+    pub incident: Option<u32>,
+    pub path: Vec<Point2D>,
+    pub path_progress: f32,
+    pub busy_until: f64, // Clock seconds
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZoneResponseStats {
+    pub incidents: u32,
+    pub responded: u32,
+    pub mean_response_time: f32, // Hours from report to arrival on scene
+    pub max_response_time: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmergencyService {
+    pub config: EmergencyConfig,
+    pub ambulances: Vec<Ambulance>,
+    pub incidents: Vec<Incident>,
+    pub response_stats: BTreeMap<String, ZoneResponseStats>,
+    pending: VecDeque<u32>,
+    next_incident_id: u32,
+}
+
+impl Ambulance {
+    pub fn is_available(&self) -> bool {
+        matches!(self.state, AmbulanceState::Idle | AmbulanceState::Returning)
+    }
+
+    pub fn has_priority(&self) -> bool {
+        matches!(self.state, AmbulanceState::Responding)
+    }
+}
+
+impl EmergencyService {
+    /// Stations the fleet at every HOSPITAL POI.
+    pub fn station_fleet(&mut self, pois: &[POI]) {
+        self.ambulances.clear();
+        self.incidents.clear();
+        self.pending.clear();
+
+        for poi in pois.iter().filter(|poi| poi.poi_type == 5) { // HOSPITAL
+            for _ in 0..self.config.ambulances_per_hospital {
+                self.ambulances.push(Ambulance {
+                    id: self.ambulances.len() as u32,
+                    hospital_id: poi.id.clone(),
+                    home: poi.position.clone(),
+                    position: poi.position.clone(),
+                    state: AmbulanceState::Idle,
+                    incident: None,
+                    path: Vec::new(),
+                    path_progress: 0.0,
+                    busy_until: 0.0,
+                });
+            }
+        }
+    }
+
+    /// Advances the service by `dt` simulated seconds to `now`, in clock seconds.
+    pub fn update(&mut self, dt: f32, now: f64, pois: &[POI], pathfinder: &PathFinder, rng: &mut impl Rng) {
+        if self.ambulances.is_empty() {
+            return;
+        }
+
+        self.generate_incidents(dt, now, pois, rng);
+        self.dispatch(pathfinder);
+        self.move_fleet(dt, now, pathfinder);
+    }
+
+    fn generate_incidents(&mut self, dt: f32, now: f64, pois: &[POI], rng: &mut impl Rng) {
+        // Poisson arrivals: at most one per tick is plenty at these rates
+        let sites: Vec<&POI> = pois.iter().filter(|poi| poi.poi_type != 5).collect();
+        if sites.is_empty() || rng.gen::<f32>() >= self.config.incident_rate * clock::hours(dt) {
+            return;
+        }
+
+        let site = sites[rng.gen_range(0..sites.len())];
+        let id = self.next_incident_id;
+        self.next_incident_id += 1;
+
+        self.incidents.push(Incident {
+            id,
+            position: site.position.clone(),
+            zone_id: site.zone_id.clone(),
+            reported_at: now,
+            assigned_to: None,
+        });
+        self.pending.push_back(id);
+        self.response_stats.entry(site.zone_id.clone()).or_default().incidents += 1;
+    }
+
+    /// Sends the nearest available ambulance to each waiting incident, oldest first.
+    fn dispatch(&mut self, pathfinder: &PathFinder) {
+        while let Some(&incident_id) = self.pending.front() {
+            let Some(incident) = self.incidents.iter_mut().find(|i| i.id == incident_id) else {
+                self.pending.pop_front();
+                continue;
+            };
+
+            let nearest = self.ambulances.iter_mut()
+                .filter(|a| a.is_available())
+                .min_by(|a, b| {
+                    let da = a.position.distance_to(&incident.position);
+                    let db = b.position.distance_to(&incident.position);
+                    da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+                });
+
+            let Some(ambulance) = nearest else {
+                break; // Everyone is busy, try again next tick
+            };
+
+            ambulance.state = AmbulanceState::Responding;
+            ambulance.incident = Some(incident_id);
+            ambulance.path = pathfinder.find_path(&ambulance.position, &incident.position);
+            ambulance.path_progress = 0.0;
+            incident.assigned_to = Some(ambulance.id);
+            self.pending.pop_front();
+        }
+    }
+
+    fn move_fleet(&mut self, dt: f32, now: f64, pathfinder: &PathFinder) {
+        let distance = clock::distance_m(self.config.ambulance_speed_kmh, dt);
+
+        for ambulance in &mut self.ambulances {
+            match ambulance.state {
+                AmbulanceState::Responding | AmbulanceState::Returning => {
+                    advance_along_path(&mut ambulance.path, &mut ambulance.path_progress, &mut ambulance.position, distance);
+                    if ambulance.path.len() > 1 {
+                        continue;
+                    }
+                    ambulance.path.clear();
+
+                    if matches!(ambulance.state, AmbulanceState::Returning) {
+                        ambulance.state = AmbulanceState::Idle;
+                        continue;
+                    }
+
+                    // Arrived on scene
+                    ambulance.state = AmbulanceState::OnScene;
+                    ambulance.busy_until = now + (self.config.on_scene_hours * clock::SECONDS_PER_HOUR) as f64;
+                    if let Some(index) = self.incidents.iter().position(|i| Some(i.id) == ambulance.incident) {
+                        let incident = self.incidents.remove(index);
+                        let response_time = clock::hours((now - incident.reported_at) as f32);
+                        let stats = self.response_stats.entry(incident.zone_id).or_default();
+                        stats.responded += 1;
+                        stats.mean_response_time += (response_time - stats.mean_response_time) / stats.responded as f32;
+                        stats.max_response_time = stats.max_response_time.max(response_time);
+                    }
+                }
+                AmbulanceState::OnScene => {
+                    if now >= ambulance.busy_until {
+                        ambulance.state = AmbulanceState::Returning;
+                        ambulance.incident = None;
+                        ambulance.path = pathfinder.find_path(&ambulance.position, &ambulance.home);
+                        ambulance.path_progress = 0.0;
+                    }
+                }
+                AmbulanceState::Idle => {}
+            }
+        }
+    }
+
+    /// Traffic near a responding ambulance pulls over and slows to a crawl.
+    pub fn apply_yielding(&self, agents: &mut [Agent], indices: &[usize]) {
+        let responders: Vec<&Point2D> = self.ambulances.iter()
+            .filter(|a| a.has_priority())
+            .map(|a| &a.position)
+            .collect();
+        if responders.is_empty() {
+            return;
+        }
+
+        for &index in indices {
+            let agent = &mut agents[index];
+            if !matches!(agent.state, AgentState::Traveling) || !CongestionField::is_motorized(&agent.agent_type) {
+                continue;
+            }
+            if responders.iter().any(|p| p.distance_to(&agent.position) < self.config.yield_radius) {
+                agent.speed_factor *= self.config.yield_factor;
+            }
+        }
+    }
+}
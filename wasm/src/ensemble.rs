@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::simulation::Simulation;
+use crate::world::CityModel;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    pub runs: u32,
+    pub base_seed: u64,       // Runs use base_seed, base_seed + 1, ...
+    pub days: u32,
+    pub sample_interval: f32, // Hours between density and popularity samples
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self {
+            runs: 10,
+            base_seed: 0,
+            days: 1,
+            sample_interval: 1.0,
+        }
+    }
+}
+
+/// What one seed produced, averaged over its samples.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    pub seed: u64,
+    pub trips_per_day: f64,
+    pub mean_trip_time: f64,                    // Hours
+    pub road_density: BTreeMap<String, f64>,    // Agents per lane
+    pub poi_popularity: BTreeMap<String, f64>,  // Agents present
+}
+
+/// Mean across runs with its 95% confidence interval.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Estimate {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsembleReport {
+    pub config: EnsembleConfig,
+    pub trips_per_day: Estimate,
+    pub mean_trip_time: Estimate,
+    pub road_density: BTreeMap<String, Estimate>,
+    pub poi_popularity: BTreeMap<String, Estimate>,
+    pub runs: Vec<RunResult>,
+}
+
+/// Runs the same scenario once per seed and aggregates the results. `setup` prepares each
+/// freshly initialized simulation, e.g. to load plans. With the parallel feature the runs
+/// share the thread pool; results are in seed order either way.
+pub fn run_ensemble<F>(city: &CityModel, config: &EnsembleConfig, setup: F) -> Result<EnsembleReport, String>
+where
+    F: Fn(&mut Simulation) -> Result<(), String> + Sync,
+{
+    let seeds: Vec<u64> = (0..config.runs.max(1) as u64).map(|run| config.base_seed + run).collect();
+    #[cfg(feature = "parallel")]
+    let seeds = seeds.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let seeds = seeds.into_iter();
+    let runs = seeds
+        .map(|seed| run_one(city, config, seed, &setup))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(EnsembleReport {
+        config: config.clone(),
+        trips_per_day: Estimate::of(runs.iter().map(|run| run.trips_per_day)),
+        mean_trip_time: Estimate::of(runs.iter().map(|run| run.mean_trip_time)),
+        road_density: aggregate(&runs, |run| &run.road_density),
+        poi_popularity: aggregate(&runs, |run| &run.poi_popularity),
+        runs,
+    })
+}
+
+fn run_one<F>(city: &CityModel, config: &EnsembleConfig, seed: u64, setup: &F) -> Result<RunResult, String>
+where
+    F: Fn(&mut Simulation) -> Result<(), String>,
+{
+    let mut simulation = Simulation::new_with_seed(seed);
+    simulation.init_with_seed(city.clone(), seed);
+    setup(&mut simulation)?;
+    simulation.start();
+
+    let interval = config.sample_interval.max(0.01);
+    let samples = ((config.days as f32 * 24.0) / interval).round().max(1.0) as u32;
+    let mut road_density: BTreeMap<String, f64> = BTreeMap::new();
+    let mut poi_popularity: BTreeMap<String, f64> = BTreeMap::new();
+    for _ in 0..samples {
+        simulation.fast_forward(interval);
+        let traffic = simulation.get_traffic_data();
+        for (road, density) in traffic.road_densities {
+            *road_density.entry(road).or_insert(0.0) += density as f64 / samples as f64;
+        }
+        for (poi, count) in traffic.poi_popularity {
+            *poi_popularity.entry(poi).or_insert(0.0) += count as f64 / samples as f64;
+        }
+    }
+
+    // Closed days plus the one the run ended in
+    let world = &simulation.world;
+    let today = world.convergence.today(world.day);
+    let days = simulation.get_learning_convergence().iter().chain(std::iter::once(&today));
+    let (trips, hours) = days.fold((0u32, 0.0f64), |(trips, hours), day| {
+        (trips + day.trips, hours + day.mean_travel_time as f64 * day.trips as f64)
+    });
+
+    Ok(RunResult {
+        seed,
+        trips_per_day: trips as f64 / config.days.max(1) as f64,
+        mean_trip_time: if trips > 0 { hours / trips as f64 } else { 0.0 },
+        road_density,
+        poi_popularity,
+    })
+}
+
+/// Per-key estimates; a key a run never saw counts as zero for that run.
+fn aggregate(runs: &[RunResult], values: impl Fn(&RunResult) -> &BTreeMap<String, f64>) -> BTreeMap<String, Estimate> {
+    let mut keys: Vec<&String> = runs.iter().flat_map(|run| values(run).keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .map(|key| {
+            let samples = runs.iter().map(|run| values(run).get(key).copied().unwrap_or(0.0));
+            (key.clone(), Estimate::of(samples))
+        })
+        .collect()
+}
+
+impl Estimate {
+    /// Student's t interval, which stays honest for the handful of runs an ensemble usually has.
+    pub fn of(samples: impl Iterator<Item = f64>) -> Self {
+        let samples: Vec<f64> = samples.collect();
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n.max(1.0);
+        if samples.len() < 2 {
+            return Self { mean, std_dev: 0.0, ci_low: mean, ci_high: mean };
+        }
+
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let std_dev = variance.sqrt();
+        let half_width = t_critical(samples.len() - 1) * std_dev / n.sqrt();
+        Self { mean, std_dev, ci_low: mean - half_width, ci_high: mean + half_width }
+    }
+}
+
+/// Two-sided 95% critical value of Student's t for `df` degrees of freedom.
+fn t_critical(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+        2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+        2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    match df {
+        0 => f64::INFINITY,
+        1..=30 => TABLE[df - 1],
+        31..=60 => 2.000,
+        61..=120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+impl EnsembleReport {
+    /// Plain-text summary: headline numbers, then the busiest roads and POIs.
+    pub fn summary(&self, top: usize) -> String {
+        let row = |e: &Estimate| format!("{:>10.3} ±{:<8.3} [{:.3}, {:.3}]", e.mean, e.ci_high - e.mean, e.ci_low, e.ci_high);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} runs (seeds {}..{}), {} day(s) each, 95% confidence intervals",
+            self.runs.len(),
+            self.config.base_seed,
+            self.config.base_seed + self.runs.len() as u64 - 1,
+            self.config.days,
+        );
+        let _ = writeln!(out, "{:<24}{}", "trips per day", row(&self.trips_per_day));
+        let _ = writeln!(out, "{:<24}{}", "trip time (h)", row(&self.mean_trip_time));
+
+        for (title, estimates) in [("road density (per lane)", &self.road_density), ("POI popularity (agents)", &self.poi_popularity)] {
+            let mut ranked: Vec<(&String, &Estimate)> = estimates.iter().collect();
+            ranked.sort_by(|a, b| b.1.mean.total_cmp(&a.1.mean).then_with(|| a.0.cmp(b.0)));
+            let _ = writeln!(out, "\n{}", title);
+            for (id, estimate) in ranked.into_iter().take(top) {
+                let _ = writeln!(out, "  {:<22}{}", id, row(estimate));
+            }
+        }
+        out
+    }
+}
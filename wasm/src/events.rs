@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    ActivityStart, // Time to plan the trip to the next activity
+    Departure,     // A planned trip is due to leave
+    DayStart,      // Nothing left to do today
+    LinkExit,      // Reaches the end of a link in the mesoscopic model
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledEvent {
+    pub time: f64, // Clock seconds
+    pub agent: usize,
+    pub kind: EventKind,
+    seq: u64,
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledEvent {}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEvent {
+    // Reversed so the max-heap pops the earliest event, ties broken by insertion order
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.time.total_cmp(&self.time).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SchedulerStats {
+    pub active_agents: u32,
+    pub sleeping_agents: u32,
+    pub queued_events: u32,
+}
+
+/// Idle agents are taken out of the per-tick loop and parked here until their next event;
+/// the rest are kept in an ordered awake set so per-tick passes only visit them. Waking an
+/// agent early leaves its old event in the heap, marked stale by its sequence number no
+/// longer matching the sleep entry. Stale events are skipped when popped and swept out once
+/// they make up most of the heap.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventQueue {
+    heap: BinaryHeap<ScheduledEvent>,
+    sleeping: BTreeMap<usize, Sleep>,
+    awake: BTreeSet<usize>,
+    population: usize, // Agent indices the queue knows about, 0..population
+    stale: usize,      // Heap entries whose agent was woken early
+    next_seq: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Sleep {
+    since: f64,
+    seq: u64, // Of the event that ends it
+}
+
+const MIN_STALE_SWEEP: usize = 64;
+
+impl EventQueue {
+    pub fn is_asleep(&self, agent: usize) -> bool {
+        self.sleeping.contains_key(&agent)
+    }
+
+    /// Awake agents in index order, the order a full scan would visit them in.
+    pub fn awake_agents(&self) -> Vec<usize> {
+        self.awake.iter().copied().collect()
+    }
+
+    /// Keeps the awake set in step with the population, which only grows or shrinks at the
+    /// end. New indices start awake; removed ones are forgotten, asleep or not.
+    pub fn set_population(&mut self, count: usize) {
+        for agent in self.population..count {
+            self.awake.insert(agent);
+        }
+        for agent in count..self.population {
+            self.awake.remove(&agent);
+            if self.sleeping.remove(&agent).is_some() {
+                self.stale += 1;
+            }
+        }
+        self.population = count;
+    }
+
+    /// Puts `agent` to sleep from `now` until `time`.
+    pub fn schedule(&mut self, agent: usize, now: f64, time: f64, kind: EventKind) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.awake.remove(&agent);
+        if self.sleeping.insert(agent, Sleep { since: now, seq }).is_some() {
+            self.stale += 1;
+        }
+        self.heap.push(ScheduledEvent { time, agent, kind, seq });
+    }
+
+    /// Wakes every agent whose event is due, returning how long each one slept.
+    pub fn pop_due(&mut self, now: f64) -> Vec<(ScheduledEvent, f64)> {
+        let mut due = Vec::new();
+        while self.heap.peek().is_some_and(|event| event.time <= now) {
+            let Some(event) = self.heap.pop() else { break };
+            let current = self.sleeping.get(&event.agent).is_some_and(|sleep| sleep.seq == event.seq);
+            if !current {
+                self.stale -= 1;
+                continue;
+            }
+            if let Some(sleep) = self.sleeping.remove(&event.agent) {
+                self.awake.insert(event.agent);
+                due.push((event, now - sleep.since));
+            }
+        }
+        due
+    }
+
+    /// Wakes one agent ahead of its event, returning how long it slept.
+    pub fn wake(&mut self, agent: usize, now: f64) -> Option<f64> {
+        let sleep = self.sleeping.remove(&agent)?;
+        self.awake.insert(agent);
+        self.stale += 1;
+        if self.stale >= MIN_STALE_SWEEP && self.stale * 2 > self.heap.len() {
+            self.sweep_stale();
+        }
+        Some(now - sleep.since)
+    }
+
+    /// Wakes everyone regardless of their events, returning how long each one slept.
+    pub fn wake_all(&mut self, now: f64) -> Vec<(usize, f64)> {
+        self.heap.clear();
+        self.stale = 0;
+        self.awake.extend(self.sleeping.keys().copied());
+        std::mem::take(&mut self.sleeping).into_iter()
+            .map(|(agent, sleep)| (agent, now - sleep.since))
+            .collect()
+    }
+
+    /// Drops heap entries of agents that were woken early. Pop order only depends on time
+    /// and sequence number, so this does not change which events come out when.
+    fn sweep_stale(&mut self) {
+        let sleeping = &self.sleeping;
+        self.heap.retain(|event| sleeping.get(&event.agent).is_some_and(|sleep| sleep.seq == event.seq));
+        self.stale = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.sleeping.clear();
+        self.awake.clear();
+        self.population = 0;
+        self.stale = 0;
+    }
+
+    pub fn stats(&self, agent_count: usize) -> SchedulerStats {
+        SchedulerStats {
+            active_agents: (agent_count - self.sleeping.len().min(agent_count)) as u32,
+            sleeping_agents: self.sleeping.len() as u32,
+            queued_events: (self.heap.len() - self.stale) as u32,
+        }
+    }
+}
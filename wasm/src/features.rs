@@ -0,0 +1,21 @@
+/// Optional subsystems in a fixed order; a subsystem's index is its bit in `mask`.
+const OPTIONAL: [(&str, bool); 5] = [
+    ("advanced-ai", cfg!(feature = "advanced-ai")),
+    ("economics", cfg!(feature = "economics")),
+    ("physics", cfg!(feature = "physics")),
+    ("scripting", cfg!(feature = "scripting")),
+    ("networking", cfg!(feature = "networking")),
+];
+
+/// Names of the optional subsystems compiled into this build.
+pub fn enabled() -> Vec<&'static str> {
+    OPTIONAL.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect()
+}
+
+/// The enabled subsystems as a bit set. Saved state only loads into a build with the same mask,
+/// since each subsystem adds its own fields to the world.
+pub fn mask() -> u32 {
+    OPTIONAL.iter().enumerate()
+        .filter(|(_, (_, on))| *on)
+        .fold(0, |mask, (bit, _)| mask | 1 << bit)
+}
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use rand::Rng;
+use crate::agent::AgentType;
+use crate::clock;
+use crate::mode_choice::{logit, sample};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningConfig {
+    pub window_hours: f32,           // Width of a departure window
+    pub max_shift_windows: i32,      // How far departures may move from the plan
+    pub route_alternatives: usize,   // Routes considered per trip
+    pub choice_scale: f32,           // Logit scale per hour of generalized cost
+    pub schedule_delay_weight: f32,  // Cost of one hour away from the planned time
+}
+
+impl Default for LearningConfig {
+    fn default() -> Self {
+        Self {
+            window_hours: 0.25,
+            max_shift_windows: 2,
+            route_alternatives: 3,
+            choice_scale: 8.0,
+            schedule_delay_weight: 0.5,
+        }
+    }
+}
+
+/// What an agent expects a trip to cost, remembered across days. Expectations are kept per
+/// `origin>target` and departure window, with one estimate for every mode and route tried
+/// there, so they survive the schedule being regenerated and a different mode being picked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TravelTimeMemory {
+    expected: BTreeMap<String, BTreeMap<u32, BTreeMap<String, Estimate>>>,
+    last_choice: BTreeMap<String, LastChoice>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct LastChoice {
+    route: usize,
+    offset: i32,
+    trips: u32, // Times this trip has been made
+}
+
+/// Running mean of the experienced travel time (method of successive averages).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Estimate {
+    seconds: f32,
+    samples: u32,
+}
+
+/// Bookkeeping for a trip in progress so it can be learned from on arrival.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripRecord {
+    pub trip_key: String, // origin>target
+    pub mode: AgentType,
+    pub route: usize,
+    pub window: u32,
+    pub departure_time: f64, // Clock seconds
+    pub expected_time: f32,  // Seconds
+}
+
+#[derive(Debug, Clone)]
+pub struct DepartureChoice {
+    pub route: usize,
+    pub offset: i32,
+    pub departure_time: f64, // Clock seconds
+    pub expected_time: f32,  // Seconds
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayConvergence {
+    pub day: u32,
+    pub trips: u32,
+    pub mean_abs_error: f32,  // Hours between expected and experienced travel time
+    pub relative_gap: f32,    // Sum of errors over sum of experienced times
+    pub switch_rate: f32,     // Share of repeated trips whose route or window changed from last time
+    pub mean_travel_time: f32, // Hours per trip
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConvergenceTracker {
+    trips: u32,
+    choices: u32,
+    switches: u32,
+    abs_error_sum: f32,   // Seconds
+    experienced_sum: f32, // Seconds
+    pub history: Vec<DayConvergence>,
+}
+
+impl TravelTimeMemory {
+    pub fn trip_key(origin: &str, target: &str) -> String {
+        format!("{}>{}", origin, target)
+    }
+
+    fn option_key(mode: &AgentType, route: usize) -> String {
+        format!("{:?}#{}", mode, route)
+    }
+
+    /// Remembered travel time in seconds in `window`, or else in the closest window this mode and route
+    /// were tried in, since schedules move a little from day to day.
+    pub fn expected(&self, trip_key: &str, mode: &AgentType, route: usize, window: u32) -> Option<f32> {
+        let option = Self::option_key(mode, route);
+        self.expected
+            .get(trip_key)?
+            .iter()
+            .filter_map(|(&tried, options)| options.get(&option).map(|estimate| (tried.abs_diff(window), estimate)))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, estimate)| estimate.seconds)
+    }
+
+    /// Moves the expectation towards the experienced time (seconds) with step 1/n, so after
+    /// n trips it is their mean and settles instead of chasing the latest day.
+    pub fn learn(&mut self, record: &TripRecord, experienced: f32) {
+        let estimate = self.expected
+            .entry(record.trip_key.clone())
+            .or_default()
+            .entry(record.window)
+            .or_default()
+            .entry(Self::option_key(&record.mode, record.route))
+            .or_insert(Estimate { seconds: record.expected_time, samples: 0 });
+
+        estimate.samples += 1;
+        estimate.seconds += (experienced - estimate.seconds) / estimate.samples as f32;
+    }
+
+    /// Stores the choice for this trip and reports whether it differs from last time, or
+    /// `None` the first time the trip is made.
+    pub fn remember_choice(&mut self, trip_key: &str, route: usize, offset: i32) -> Option<bool> {
+        let previous = self.last_choice.get(trip_key).copied();
+        let trips = previous.map_or(0, |last| last.trips) + 1;
+        self.last_choice.insert(trip_key.to_string(), LastChoice { route, offset, trips });
+
+        previous.map(|last| (last.route, last.offset) != (route, offset))
+    }
+}
+
+impl LearningConfig {
+    /// Departure window the time of day at `time` clock seconds falls in.
+    pub fn window_of(&self, time: f64) -> u32 {
+        let seconds_of_day = time.rem_euclid(clock::SECONDS_PER_DAY) as f32;
+        (clock::hours(seconds_of_day) / self.window_hours) as u32
+    }
+
+    /// How early before a planned activity the agent has to start deciding.
+    pub fn departure_lead(&self) -> f32 {
+        self.max_shift_windows as f32 * self.window_hours
+    }
+
+    /// Logit over route alternatives and departure windows around `planned_time` (clock
+    /// seconds, never before `now`), using remembered travel times for `mode` where available
+    /// and the `free_flow` seconds otherwise.
+    /// A trip made n times before is only reconsidered with probability 1/(n+1); otherwise the
+    /// agent repeats its last route and window, so choices settle along with the expectations.
+    #[allow(clippy::too_many_arguments)]
+    pub fn choose(
+        &self,
+        memory: &TravelTimeMemory,
+        trip_key: &str,
+        mode: &AgentType,
+        planned_time: f64,
+        now: f64,
+        free_flow: &[f32],
+        rng: &mut impl Rng,
+    ) -> DepartureChoice {
+        let mut options = Vec::new();
+        let window_seconds = (self.window_hours * clock::SECONDS_PER_HOUR) as f64;
+
+        for (route, &free_flow_time) in free_flow.iter().enumerate() {
+            for offset in -self.max_shift_windows..=self.max_shift_windows {
+                let departure_time = (planned_time + offset as f64 * window_seconds).max(now);
+                let window = self.window_of(departure_time);
+                let expected_time = memory.expected(trip_key, mode, route, window).unwrap_or(free_flow_time);
+                let delay = clock::hours((departure_time - planned_time).abs() as f32);
+                let cost = clock::hours(expected_time) + self.schedule_delay_weight * delay;
+
+                options.push((
+                    DepartureChoice { route, offset, departure_time, expected_time },
+                    -self.choice_scale * cost,
+                ));
+            }
+        }
+
+        if let Some(last) = memory.last_choice.get(trip_key) {
+            let repeat = options.iter().position(|(choice, _)| (choice.route, choice.offset) == (last.route, last.offset));
+            if let Some(i) = repeat.filter(|_| rng.gen::<f32>() * (last.trips + 1) as f32 >= 1.0) {
+                return options.swap_remove(i).0;
+            }
+        }
+
+        let utilities: Vec<f32> = options.iter().map(|(_, u)| *u).collect();
+        match sample(&logit(&utilities), rng) {
+            Some(i) => options.swap_remove(i).0,
+            None => DepartureChoice { route: 0, offset: 0, departure_time: now, expected_time: 0.0 },
+        }
+    }
+}
+
+impl ConvergenceTracker {
+    pub fn record_choice(&mut self, switched: bool) {
+        self.choices += 1;
+        if switched {
+            self.switches += 1;
+        }
+    }
+
+    /// Both in seconds.
+    pub fn record_trip(&mut self, expected: f32, experienced: f32) {
+        self.trips += 1;
+        self.abs_error_sum += (experienced - expected).abs();
+        self.experienced_sum += experienced;
+    }
+
+    /// Summary of the day still in progress.
+    pub fn today(&self, day: u32) -> DayConvergence {
+        let trips = self.trips.max(1) as f32;
+        DayConvergence {
+            day,
+            trips: self.trips,
+            mean_abs_error: clock::hours(self.abs_error_sum / trips),
+            relative_gap: if self.experienced_sum > 0.0 { self.abs_error_sum / self.experienced_sum } else { 0.0 },
+            switch_rate: self.switches as f32 / self.choices.max(1) as f32,
+            mean_travel_time: clock::hours(self.experienced_sum / trips),
+        }
+    }
+
+    /// Summarizes the finished day and resets the accumulators.
+    pub fn close_day(&mut self, day: u32) {
+        self.history.push(self.today(day));
+
+        self.trips = 0;
+        self.choices = 0;
+        self.switches = 0;
+        self.abs_error_sum = 0.0;
+        self.experienced_sum = 0.0;
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use crate::adaptive_scaling::AdaptiveScaler;
+use crate::performance::PerformanceProfile;
+
+const LOG_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingConfig {
+    pub cooldown_ms: f64,          // Wall time after an adjustment before the next one
+    pub reversal_cooldown_ms: f64, // Longer wait before undoing the previous adjustment
+}
+
+impl Default for ScalingConfig {
+    fn default() -> Self {
+        Self {
+            cooldown_ms: 5000.0,
+            reversal_cooldown_ms: 15000.0,
+        }
+    }
+}
+
+/// One adjustment applied to the running simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingEvent {
+    pub sim_seconds: f64,
+    pub fps: f64,                      // Frame rate reported when it was applied
+    pub max_agents: (u32, u32),        // Before, after
+    pub update_frequency: (u32, u32),  // Hz
+    pub render_distance: (f32, f32),   // Meters
+}
+
+/// Turns the adaptive scaler's recommendations into changes to apply. The scaler already
+/// waits for a stable frame rate well outside its target; on top of that each adjustment is
+/// held for a while, and longer before going back the other way, so a frame rate that
+/// hovers near the threshold does not keep adding and removing agents.
+pub struct LiveScaling {
+    pub scaler: AdaptiveScaler,
+    pub config: ScalingConfig,
+    applied: PerformanceProfile,
+    last_direction: i32,
+    since_change_ms: f64,
+    log: Vec<ScalingEvent>,
+}
+
+impl LiveScaling {
+    pub fn new(profile: PerformanceProfile) -> Self {
+        Self {
+            scaler: AdaptiveScaler::new_with_profile(profile.clone()),
+            config: ScalingConfig::default(),
+            applied: profile,
+            last_direction: 0,
+            since_change_ms: 0.0,
+            log: Vec::new(),
+        }
+    }
+
+    /// Feeds one rendered frame, returning the profile to switch to when an adjustment is due.
+    pub fn observe_frame(&mut self, fps: f64, frame_ms: f64) -> Option<PerformanceProfile> {
+        self.scaler.update_fps(fps, frame_ms);
+        self.since_change_ms += frame_ms;
+
+        let proposed = self.scaler.get_current_profile();
+        let direction = direction(&self.applied, &proposed);
+        if direction == 0 {
+            return None;
+        }
+        let hold = if direction == -self.last_direction {
+            self.config.reversal_cooldown_ms
+        } else {
+            self.config.cooldown_ms
+        };
+        if self.since_change_ms < hold {
+            return None;
+        }
+
+        self.applied = proposed.clone();
+        self.last_direction = direction;
+        self.since_change_ms = 0.0;
+        Some(proposed)
+    }
+
+    pub fn record(&mut self, event: ScalingEvent) {
+        if self.log.len() == LOG_CAPACITY {
+            self.log.remove(0);
+        }
+        self.log.push(event);
+    }
+
+    /// Most recent adjustments, oldest first.
+    pub fn log(&self) -> &[ScalingEvent] {
+        &self.log
+    }
+}
+
+/// 1 if `to` asks more of the device than `from`, -1 if less, 0 if they match.
+fn direction(from: &PerformanceProfile, to: &PerformanceProfile) -> i32 {
+    let by_agents = to.max_agents().cmp(&from.max_agents());
+    let by_frequency = to.update_frequency().cmp(&from.update_frequency());
+    let by_distance = to.render_distance().total_cmp(&from.render_distance());
+    by_agents.then(by_frequency).then(by_distance) as i32
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::agent::{Agent, Point2D};
+use crate::traffic::CongestionField;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MesoConfig {
+    pub hysteresis: f32,         // Meters past the area's edge before an agent drops to meso
+    pub link_headway: f32,       // Seconds between vehicles leaving the same link
+}
+
+impl Default for MesoConfig {
+    fn default() -> Self {
+        Self {
+            hysteresis: 100.0,
+            link_headway: 2.0,
+        }
+    }
+}
+
+/// Where agents are simulated in full, usually what the camera is looking at.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AreaOfInterest {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32, // Meters
+}
+
+impl AreaOfInterest {
+    fn distance(&self, position: &Point2D) -> f32 {
+        Point2D::new(self.x, self.y).distance_to(position)
+    }
+}
+
+/// An agent crossing one link without being stepped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MesoLeg {
+    pub from: Point2D,
+    pub to: Point2D,
+    pub start_progress: f32, // Agent's progress along the link when it entered
+    pub entered_at: f64,     // Clock seconds
+    pub exit_at: f64,
+}
+
+impl MesoLeg {
+    pub fn length(&self) -> f32 {
+        self.from.distance_to(&self.to)
+    }
+
+    /// Where the agent is at `now`, assuming constant speed along the leg.
+    pub fn position_at(&self, now: f64) -> Point2D {
+        let t = self.fraction(now);
+        Point2D::new(self.from.x + (self.to.x - self.from.x) * t, self.from.y + (self.to.y - self.from.y) * t)
+    }
+
+    /// Share of the leg covered at `now`.
+    pub fn fraction(&self, now: f64) -> f32 {
+        let duration = self.exit_at - self.entered_at;
+        if duration <= 0.0 {
+            return 1.0;
+        }
+        ((now - self.entered_at) / duration).clamp(0.0, 1.0) as f32
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LodStats {
+    pub micro_agents: u32, // Traveling and stepped every tick
+    pub meso_agents: u32,  // Traveling link by link in the queue model
+}
+
+/// Mesoscopic travel outside the area of interest. Agents there cross a whole link per event:
+/// free-flow time at their current speed, held back when the link's exit is still busy with
+/// the vehicle ahead. Without an area everyone is simulated in full.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MesoModel {
+    pub config: MesoConfig,
+    pub area: Option<AreaOfInterest>,
+    pub legs: BTreeMap<usize, MesoLeg>, // By agent index
+    next_exit: BTreeMap<(i32, i32, i32, i32), f64>, // Earliest next exit per link
+}
+
+impl MesoModel {
+    /// Agents this far out or further are handed to the queue model.
+    pub fn is_outside(&self, position: &Point2D) -> bool {
+        self.area.is_some_and(|area| area.distance(position) > area.radius + self.config.hysteresis)
+    }
+
+    /// Agents this close get full simulation back.
+    pub fn is_inside(&self, position: &Point2D) -> bool {
+        self.area.is_none_or(|area| area.distance(position) <= area.radius)
+    }
+
+    /// Starts `agent` on the rest of its current link, returning when it comes out the end.
+    pub fn enter_link(&mut self, index: usize, agent: &Agent, now: f64) -> Option<f64> {
+        let (start, end) = (agent.path.first()?, agent.path.get(1)?);
+        let speed = (agent.speed * agent.speed_factor).max(0.1) / 3.6; // m/s
+        let leg = MesoLeg {
+            from: agent.position.clone(),
+            to: end.clone(),
+            start_progress: agent.path_progress,
+            entered_at: now,
+            exit_at: 0.0,
+        };
+
+        let mut exit_at = now + (leg.length() / speed) as f64;
+        if CongestionField::is_motorized(&agent.agent_type) {
+            let key = (start.x.round() as i32, start.y.round() as i32, end.x.round() as i32, end.y.round() as i32);
+            let free = self.next_exit.entry(key).or_insert(0.0);
+            exit_at = exit_at.max(*free);
+            *free = exit_at + self.config.link_headway as f64;
+        }
+
+        self.legs.insert(index, MesoLeg { exit_at, ..leg });
+        Some(exit_at)
+    }
+
+    /// Moves queued agents to where they would be by now, so they still render and add to
+    /// congestion in about the right place.
+    pub fn interpolate(&self, agents: &mut [Agent], now: f64) {
+        for (&index, leg) in &self.legs {
+            agents[index].position = leg.position_at(now);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.legs.clear();
+        self.next_exit.clear();
+    }
+}
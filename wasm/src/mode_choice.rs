@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use rand::Rng;
+use crate::agent::AgentType;
+use crate::clock;
+
+/// Modes an agent can pick from for a single trip.
+pub const CHOICE_SET: [AgentType; 6] = [
+    AgentType::Pedestrian,
+    AgentType::Car,
+    AgentType::Bus,
+    AgentType::Bicycle,
+    AgentType::RideHail,
+    AgentType::Autonomous,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeParams {
+    pub speed_kmh: f32,          // Average door-to-door speed on the network
+    pub constant: f32,           // Alternative specific constant (utils)
+    pub cost_per_km: f32,        // Running cost
+    pub fixed_cost: f32,         // Fare, parking, etc.
+    pub fixed_time: f32,         // Waiting/access time in hours
+    pub max_distance_km: f32,    // Beyond this the mode is not offered
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeChoiceModel {
+    pub walk: ModeParams,
+    pub car: ModeParams,
+    pub bus: ModeParams,
+    pub bike: ModeParams,
+    pub ride_hail: ModeParams,
+    pub autonomous: ModeParams,
+    pub beta_time: f32,           // Utils per hour of travel time
+    pub beta_cost: f32,           // Utils per unit of money
+    pub car_ownership_rate: f32,  // Share of agents with a car available
+}
+
+/// Inputs the logit needs for one trip.
+#[derive(Debug, Clone)]
+pub struct TripContext {
+    pub network_distance: f32, // Meters along the routed path
+    pub owns_car: bool,
+    pub owns_av: bool,
+    pub ride_hail_available: bool,
+}
+
+impl Default for ModeChoiceModel {
+    fn default() -> Self {
+        Self {
+            walk: ModeParams {
+                speed_kmh: 5.0,
+                constant: 0.0,
+                cost_per_km: 0.0,
+                fixed_cost: 0.0,
+                fixed_time: 0.0,
+                max_distance_km: 4.0,
+            },
+            car: ModeParams {
+                speed_kmh: 30.0,
+                constant: 0.5,
+                cost_per_km: 0.3,
+                fixed_cost: 2.0,
+                fixed_time: 0.05,
+                max_distance_km: f32::INFINITY,
+            },
+            bus: ModeParams {
+                speed_kmh: 18.0,
+                constant: -0.3,
+                cost_per_km: 0.0,
+                fixed_cost: 2.5,
+                fixed_time: 0.15,
+                max_distance_km: f32::INFINITY,
+            },
+            bike: ModeParams {
+                speed_kmh: 15.0,
+                constant: -0.5,
+                cost_per_km: 0.0,
+                fixed_cost: 0.0,
+                fixed_time: 0.0,
+                max_distance_km: 15.0,
+            },
+            ride_hail: ModeParams {
+                speed_kmh: 30.0,
+                constant: -0.8,
+                cost_per_km: 1.2,
+                fixed_cost: 3.0,
+                fixed_time: 0.1,
+                max_distance_km: f32::INFINITY,
+            },
+            autonomous: ModeParams {
+                speed_kmh: 30.0,
+                constant: 0.8,
+                cost_per_km: 0.3,
+                fixed_cost: 0.0,
+                fixed_time: 0.05,
+                max_distance_km: f32::INFINITY,
+            },
+            beta_time: -4.0,
+            beta_cost: -0.25,
+            car_ownership_rate: 0.7,
+        }
+    }
+}
+
+impl ModeChoiceModel {
+    pub fn params(&self, mode: &AgentType) -> Option<&ModeParams> {
+        match mode {
+            AgentType::Pedestrian => Some(&self.walk),
+            AgentType::Car => Some(&self.car),
+            AgentType::Bus => Some(&self.bus),
+            AgentType::Bicycle => Some(&self.bike),
+            AgentType::RideHail => Some(&self.ride_hail),
+            AgentType::Autonomous => Some(&self.autonomous),
+            _ => None,
+        }
+    }
+
+    /// Travel time in hours for a routed trip using the given mode.
+    pub fn travel_time(&self, mode: &AgentType, network_distance: f32) -> f32 {
+        match self.params(mode) {
+            Some(p) => p.fixed_time + clock::hours(self.moving_time(mode, network_distance)),
+            None => f32::INFINITY,
+        }
+    }
+
+    /// Seconds spent moving along the route at free flow, without waiting or access time.
+    /// This is the part of the trip agents actually live through, departure to arrival.
+    pub fn moving_time(&self, mode: &AgentType, network_distance: f32) -> f32 {
+        match self.params(mode) {
+            Some(p) => network_distance / (p.speed_kmh / 3.6),
+            None => f32::INFINITY,
+        }
+    }
+
+    /// Systematic utility of a mode, or `None` when it is not available for this trip.
+    pub fn utility(&self, mode: &AgentType, trip: &TripContext) -> Option<f32> {
+        let p = self.params(mode)?;
+        let distance_km = trip.network_distance / 1000.0;
+
+        if distance_km > p.max_distance_km {
+            return None;
+        }
+        if matches!(mode, AgentType::Car) && !trip.owns_car {
+            return None;
+        }
+        if matches!(mode, AgentType::Autonomous) && !trip.owns_av {
+            return None;
+        }
+        if matches!(mode, AgentType::RideHail) && !trip.ride_hail_available {
+            return None;
+        }
+
+        let time = self.travel_time(mode, trip.network_distance);
+        let cost = p.fixed_cost + p.cost_per_km * distance_km;
+        Some(p.constant + self.beta_time * time + self.beta_cost * cost)
+    }
+
+    /// Multinomial logit probabilities over the choice set.
+    pub fn probabilities(&self, trip: &TripContext) -> Vec<(AgentType, f32)> {
+        let (modes, utilities): (Vec<AgentType>, Vec<f32>) = CHOICE_SET
+            .iter()
+            .filter_map(|mode| self.utility(mode, trip).map(|u| (*mode, u)))
+            .unzip();
+
+        modes.into_iter().zip(logit(&utilities)).collect()
+    }
+
+    /// Samples a mode for the trip. Falls back to walking when nothing else is offered.
+    pub fn choose(&self, trip: &TripContext, rng: &mut impl Rng) -> AgentType {
+        let probabilities = self.probabilities(trip);
+        let shares: Vec<f32> = probabilities.iter().map(|(_, p)| *p).collect();
+
+        sample(&shares, rng)
+            .map(|i| probabilities[i].0)
+            .unwrap_or(AgentType::Pedestrian)
+    }
+}
+
+/// Multinomial logit probabilities for the given utilities, in the same order.
+pub fn logit(utilities: &[f32]) -> Vec<f32> {
+    // Subtract the max utility for numerical stability
+    let max_u = utilities.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let weights: Vec<f32> = utilities.iter().map(|u| (u - max_u).exp()).collect();
+    let total: f32 = weights.iter().sum();
+
+    weights.into_iter().map(|w| w / total).collect()
+}
+
+/// Draws an index according to `probabilities`; the last one absorbs rounding. `None` when empty.
+pub fn sample(probabilities: &[f32], rng: &mut impl Rng) -> Option<usize> {
+    let mut draw = rng.gen::<f32>();
+
+    for (i, p) in probabilities.iter().enumerate() {
+        if draw < *p {
+            return Some(i);
+        }
+        draw -= p;
+    }
+
+    probabilities.len().checked_sub(1)
+}
@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use crate::agent::Point2D;
 use crate::world::Road;
-use std::collections::{HashMap, BinaryHeap};
+use std::collections::{BTreeMap, HashSet, BinaryHeap};
 use std::cmp::Ordering;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -24,16 +25,15 @@ impl PartialOrd for State {
     }
 }
 
-#[allow(dead_code)]
+#[derive(Serialize, Deserialize)]
 pub struct PathFinder {
-    road_graph: HashMap<usize, Vec<(usize, f32)>>,
+    road_graph: BTreeMap<usize, Vec<(usize, f32)>>,
     road_nodes: Vec<Point2D>,
 }
 
-#[allow(dead_code)]
 impl PathFinder {
     pub fn new(roads: &[Road]) -> Self {
-        let mut road_graph = HashMap::new();
+        let mut road_graph = BTreeMap::new();
         let mut road_nodes = Vec::new();
 
         // Build simplified road network
@@ -63,8 +63,11 @@ impl PathFinder {
         }
     }
 
-    #[allow(dead_code)]
     pub fn find_path(&self, start: &Point2D, end: &Point2D) -> Vec<Point2D> {
+        if self.road_nodes.is_empty() {
+            return vec![start.clone(), end.clone()];
+        }
+
         let start_node = self.find_nearest_node(start);
         let end_node = self.find_nearest_node(end);
 
@@ -80,6 +83,45 @@ impl PathFinder {
         }
     }
 
+    /// Up to `k` distinct routes, found by penalizing the edges of earlier ones.
+    pub fn find_alternative_paths(&self, start: &Point2D, end: &Point2D, k: usize) -> Vec<Vec<Point2D>> {
+        if self.road_nodes.is_empty() || k <= 1 {
+            return vec![self.find_path(start, end)];
+        }
+
+        let start_node = self.find_nearest_node(start);
+        let end_node = self.find_nearest_node(end);
+        let mut penalized = HashSet::new();
+        let mut seen: Vec<Vec<usize>> = Vec::new();
+        let mut paths = Vec::new();
+
+        for _ in 0..k {
+            let Some(path_nodes) = self.dijkstra_penalized(start_node, end_node, &penalized, 1.5) else {
+                break;
+            };
+            if seen.contains(&path_nodes) {
+                continue;
+            }
+
+            let mut previous = start_node;
+            for &node in &path_nodes {
+                penalized.insert((previous, node));
+                previous = node;
+            }
+
+            let mut path = vec![start.clone()];
+            path.extend(path_nodes.iter().map(|&i| self.road_nodes[i].clone()));
+            path.push(end.clone());
+            paths.push(path);
+            seen.push(path_nodes);
+        }
+
+        if paths.is_empty() {
+            paths.push(vec![start.clone(), end.clone()]);
+        }
+        paths
+    }
+
     #[allow(dead_code)]
     fn find_nearest_node(&self, point: &Point2D) -> usize {
         self.road_nodes
@@ -92,6 +134,16 @@ impl PathFinder {
 
     #[allow(dead_code)]
     fn dijkstra(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        self.dijkstra_penalized(start, goal, &HashSet::new(), 1.0)
+    }
+
+    fn dijkstra_penalized(
+        &self,
+        start: usize,
+        goal: usize,
+        penalized: &HashSet<(usize, usize)>,
+        penalty: f32,
+    ) -> Option<Vec<usize>> {
         let mut dist = vec![f32::INFINITY; self.road_nodes.len()];
         let mut prev = vec![None; self.road_nodes.len()];
         let mut heap = BinaryHeap::new();
@@ -117,6 +169,11 @@ impl PathFinder {
 
             if let Some(neighbors) = self.road_graph.get(&position) {
                 for &(neighbor, edge_cost) in neighbors {
+                    let edge_cost = if penalized.contains(&(position, neighbor)) {
+                        edge_cost * penalty
+                    } else {
+                        edge_cost
+                    };
                     let next = State {
                         cost: cost + edge_cost,
                         position: neighbor,
@@ -134,10 +191,42 @@ impl PathFinder {
         None
     }
 
+    /// Total length of a polyline path
+    pub fn path_length(path: &[Point2D]) -> f32 {
+        path.windows(2).map(|w| Self::distance(&w[0], &w[1])).sum()
+    }
+
     #[allow(dead_code)]
     fn distance(a: &Point2D, b: &Point2D) -> f32 {
         let dx = a.x - b.x;
         let dy = a.y - b.y;
         (dx * dx + dy * dy).sqrt()
     }
-}
\ No newline at end of file
+}
+
+/// Moves `position` `distance` meters along `path`, dropping segments as they are completed.
+/// `progress` is the fraction of the first segment already covered.
+pub fn advance_along_path(path: &mut Vec<Point2D>, progress: &mut f32, position: &mut Point2D, distance: f32) {
+    let mut remaining = distance;
+
+    while path.len() > 1 && remaining > 0.0 {
+        let start = &path[0];
+        let end = &path[1];
+        let segment_length = ((end.x - start.x).powi(2) + (end.y - start.y).powi(2)).sqrt();
+        let left_on_segment = segment_length * (1.0 - *progress);
+
+        if remaining >= left_on_segment {
+            // Move to next path segment
+            remaining -= left_on_segment;
+            path.remove(0);
+            *progress = 0.0;
+            *position = path[0].clone();
+        } else {
+            // Interpolate position along current path segment
+            *progress += remaining / segment_length;
+            remaining = 0.0;
+            position.x = start.x + (end.x - start.x) * *progress;
+            position.y = start.y + (end.y - start.y) * *progress;
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PerformanceProfile {
     target_fps: u32,           // 15, 30, 60, 120, 144, 165, 240+
     max_agents: u32,           // Dynamic based on benchmark
@@ -11,29 +13,27 @@ pub struct PerformanceProfile {
     culling_enabled: bool,     // Frustum culling for performance
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl PerformanceProfile {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> PerformanceProfile {
         Self::default()
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn auto_detect() -> PerformanceProfile {
-        let mut profile = PerformanceProfile::default();
-
         // Start conservative, benchmark will adjust
-        profile.target_fps = 60;
-        profile.max_agents = 5000;
-        profile.render_distance = 1000.0;
-        profile.update_frequency = 60;
-        profile.lod_levels = 3;
-        profile.culling_enabled = true;
-
-        profile
+        PerformanceProfile {
+            target_fps: 60,
+            max_agents: 5000,
+            render_distance: 1000.0,
+            update_frequency: 60,
+            lod_levels: 3,
+            culling_enabled: true,
+        }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn for_ultra_gaming() -> PerformanceProfile {
         PerformanceProfile {
             target_fps: 240,
@@ -45,7 +45,7 @@ impl PerformanceProfile {
         }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn for_high_end() -> PerformanceProfile {
         PerformanceProfile {
             target_fps: 144,
@@ -57,7 +57,7 @@ impl PerformanceProfile {
         }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn for_standard() -> PerformanceProfile {
         PerformanceProfile {
             target_fps: 60,
@@ -69,7 +69,7 @@ impl PerformanceProfile {
         }
     }
 
-    #[wasm_bindgen]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
     pub fn for_mobile() -> PerformanceProfile {
         PerformanceProfile {
             target_fps: 30,
@@ -82,41 +82,41 @@ impl PerformanceProfile {
     }
 
     // Getters for wasm_bindgen
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn target_fps(&self) -> u32 { self.target_fps }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn max_agents(&self) -> u32 { self.max_agents }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn render_distance(&self) -> f32 { self.render_distance }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn update_frequency(&self) -> u32 { self.update_frequency }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn lod_levels(&self) -> u32 { self.lod_levels }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn culling_enabled(&self) -> bool { self.culling_enabled }
 
     // Setters for wasm_bindgen
-    #[wasm_bindgen(setter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(setter))]
     pub fn set_target_fps(&mut self, value: u32) { self.target_fps = value; }
 
-    #[wasm_bindgen(setter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(setter))]
     pub fn set_max_agents(&mut self, value: u32) { self.max_agents = value; }
 
-    #[wasm_bindgen(setter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(setter))]
     pub fn set_render_distance(&mut self, value: f32) { self.render_distance = value; }
 
-    #[wasm_bindgen(setter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(setter))]
     pub fn set_update_frequency(&mut self, value: u32) { self.update_frequency = value; }
 
-    #[wasm_bindgen(setter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(setter))]
     pub fn set_lod_levels(&mut self, value: u32) { self.lod_levels = value; }
 
-    #[wasm_bindgen(setter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(setter))]
     pub fn set_culling_enabled(&mut self, value: bool) { self.culling_enabled = value; }
 }
 
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::agent::ScheduleEntry;
+use crate::world::POI;
+
+/// Externally supplied travel demand, e.g. converted from a travel survey or a MATSim plans file.
+/// Each person becomes one agent that repeats its plan every day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanSet {
+    pub persons: Vec<PersonPlan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonPlan {
+    pub id: String,
+    #[serde(default)]
+    pub home_poi_id: Option<String>,  // Defaults to the first activity's POI
+    #[serde(default)]
+    pub owns_car: Option<bool>,       // Drawn from the mode choice model when missing
+    pub activities: Vec<PlannedActivity>,
+}
+
+/// One activity in a plan. Only the first activity may leave out its start time,
+/// in which case it is where the person wakes up and `end_time` is when they leave.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedActivity {
+    pub poi_id: String,
+    #[serde(default)]
+    pub activity_type: Option<String>, // Informational, the POI decides the type
+    #[serde(default)]
+    pub start_time: Option<PlanTime>,
+    #[serde(default)]
+    pub end_time: Option<PlanTime>,
+    #[serde(default)]
+    pub duration: Option<PlanTime>,
+}
+
+/// Either hours since midnight or a "HH:MM[:SS]" clock string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PlanTime {
+    Hours(f32),
+    Clock(String),
+}
+
+impl PlanTime {
+    pub fn hours(&self) -> Result<f32, String> {
+        match self {
+            PlanTime::Hours(hours) => Ok(*hours),
+            PlanTime::Clock(clock) => {
+                let parts: Vec<&str> = clock.split(':').collect();
+                if parts.len() < 2 || parts.len() > 3 {
+                    return Err(format!("invalid time \"{}\"", clock));
+                }
+                let mut hours = 0.0;
+                for (part, scale) in parts.iter().zip([1.0, 60.0, 3600.0]) {
+                    let value: f32 = part.trim().parse()
+                        .map_err(|_| format!("invalid time \"{}\"", clock))?;
+                    hours += value / scale;
+                }
+                Ok(hours)
+            }
+        }
+    }
+}
+
+/// A plan resolved against the city, ready to be handed to an agent.
+#[derive(Debug, Clone)]
+pub struct ResolvedPlan {
+    pub person_id: String,
+    pub home_poi_id: String,
+    pub owns_car: Option<bool>,
+    pub schedule: Vec<ScheduleEntry>,
+}
+
+impl PersonPlan {
+    pub fn resolve(&self, pois: &[POI], poi_lookup: &BTreeMap<String, usize>) -> Result<ResolvedPlan, String> {
+        let poi = |id: &str| {
+            poi_lookup.get(id)
+                .map(|&i| &pois[i])
+                .ok_or_else(|| format!("person {}: unknown POI \"{}\"", self.id, id))
+        };
+
+        let first = self.activities.first()
+            .ok_or_else(|| format!("person {}: plan has no activities", self.id))?;
+        let home_poi_id = self.home_poi_id.clone().unwrap_or_else(|| first.poi_id.clone());
+        poi(&home_poi_id)?;
+
+        let mut schedule = Vec::new();
+        let mut previous_end: Option<f32> = None;
+
+        for (i, activity) in self.activities.iter().enumerate() {
+            let target = poi(&activity.poi_id)?;
+            let start = Self::optional_hours(&activity.start_time)?;
+            let end = Self::optional_hours(&activity.end_time)?;
+            let duration = Self::optional_hours(&activity.duration)?;
+
+            if i == 0 && start.is_none() {
+                // The person starts the day here, so there is no trip to it
+                previous_end = end;
+                continue;
+            }
+
+            let Some(start) = start.or(previous_end) else {
+                return Err(format!("person {}: activity {} has no start time", self.id, i));
+            };
+            let duration = duration
+                .or(end.map(|end| end - start))
+                .unwrap_or(24.0 - start)
+                .max(0.0);
+
+            schedule.push(ScheduleEntry {
+                poi_type: target.poi_type,
+                start_time: start,
+                duration,
+                preferred_poi_id: Some(target.id.clone()),
+            });
+            previous_end = Some(start + duration);
+        }
+
+        Ok(ResolvedPlan {
+            person_id: self.id.clone(),
+            home_poi_id,
+            owns_car: self.owns_car,
+            schedule,
+        })
+    }
+
+    fn optional_hours(time: &Option<PlanTime>) -> Result<Option<f32>, String> {
+        time.as_ref().map(PlanTime::hours).transpose()
+    }
+}
@@ -0,0 +1,46 @@
+//! The few things the simulation needs from its host. In the browser they go through
+//! js-sys/web-sys; natively they use std, even with the wasm feature on, so the headless
+//! runner and the tests work with the default features.
+
+/// Wall-clock milliseconds since the Unix epoch.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+pub fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub fn log(message: &str) {
+    web_sys::console::log_1(&message.into());
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+pub fn log(message: &str) {
+    eprintln!("{}", message);
+}
+
+/// Whether data-parallel work may go to the rayon pool. In the browser only once
+/// `init_thread_pool` has started the workers, and never from the main thread, which is not
+/// allowed to block waiting for them; elsewhere it runs serially on the calling thread.
+#[cfg(all(feature = "parallel", feature = "wasm", target_arch = "wasm32", target_feature = "atomics"))]
+pub fn can_use_threads() -> bool {
+    let in_worker = js_sys::Reflect::has(&js_sys::global(), &"WorkerGlobalScope".into()).unwrap_or(false);
+    crate::threads::is_running() && in_worker
+}
+
+#[cfg(all(feature = "parallel", feature = "wasm", target_arch = "wasm32", not(target_feature = "atomics")))]
+pub fn can_use_threads() -> bool {
+    false
+}
+
+#[cfg(all(feature = "parallel", not(all(feature = "wasm", target_arch = "wasm32"))))]
+pub fn can_use_threads() -> bool {
+    true
+}
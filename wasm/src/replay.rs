@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use crate::agent::Agent;
+use crate::meso::AreaOfInterest;
+#[cfg(feature = "scripting")]
+use crate::plans::PlanSet;
+
+const MAGIC: &[u8; 4] = b"USRC";
+pub const RECORDING_VERSION: u32 = 3;
+
+/// Everything from outside that can change how a run unfolds. Replaying the same inputs
+/// in order on the same initial snapshot reproduces the run exactly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InputEvent {
+    Start,
+    Pause,
+    SetSpeed(f32),
+    SetTimeScale(f64),
+    Tick { elapsed_ms: Option<f64> },
+    FastForward(f32),
+    SetAreaOfInterest(Option<AreaOfInterest>),
+    SetPopulationCap(Option<u32>),
+    SetUpdateFrequency(u32),
+    #[cfg(feature = "scripting")]
+    LoadPlans(PlanSet),
+}
+
+/// Agents that changed since the previous frame, as parallel arrays.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrajectoryFrame {
+    pub time: f64,        // Clock seconds
+    pub indices: Vec<u32>,
+    pub positions: Vec<f32>, // x, y per changed agent
+    pub states: Vec<u8>,
+}
+
+/// Agent movement sampled at a fixed sim-time interval, stored as deltas so sleeping
+/// agents take no space. Enough to play a run back without simulating it again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Trajectory {
+    pub interval_seconds: f64,
+    pub agent_count: u32,
+    pub frames: Vec<TrajectoryFrame>,
+    #[serde(skip)]
+    last: Vec<(f32, f32, u8)>,
+    #[serde(skip)]
+    next_frame_at: f64,
+}
+
+impl Trajectory {
+    pub fn new(interval_seconds: f64) -> Self {
+        Self { interval_seconds: interval_seconds.max(1.0), ..Self::default() }
+    }
+
+    pub fn capture(&mut self, time: f64, agents: &[Agent]) {
+        if time < self.next_frame_at {
+            return;
+        }
+        self.next_frame_at = time + self.interval_seconds;
+
+        if self.last.len() != agents.len() {
+            // First frame, or the population was replaced: send everyone
+            self.last = vec![(f32::NAN, f32::NAN, u8::MAX); agents.len()];
+            self.agent_count = agents.len() as u32;
+        }
+
+        let mut frame = TrajectoryFrame { time, ..TrajectoryFrame::default() };
+        for (index, agent) in agents.iter().enumerate() {
+            let current = (agent.position.x, agent.position.y, agent.state.code());
+            let last = self.last[index];
+            if last.0 == current.0 && last.1 == current.1 && last.2 == current.2 {
+                continue;
+            }
+            self.last[index] = current;
+            frame.indices.push(index as u32);
+            frame.positions.extend([current.0, current.1]);
+            frame.states.push(current.2);
+        }
+        self.frames.push(frame);
+    }
+}
+
+/// Initial snapshot plus every input after it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub initial_state: Vec<u8>,
+    pub inputs: Vec<InputEvent>,
+    pub trajectory: Option<Trajectory>,
+}
+
+impl Recording {
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let body = bincode::serialize(self).map_err(|e| format!("Failed to save recording: {}", e))?;
+
+        let mut blob = Vec::with_capacity(MAGIC.len() + 4 + body.len());
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&RECORDING_VERSION.to_le_bytes());
+        blob.extend_from_slice(&body);
+        Ok(blob)
+    }
+
+    pub fn decode(blob: &[u8]) -> Result<Self, String> {
+        if blob.len() < MAGIC.len() + 4 || &blob[..MAGIC.len()] != MAGIC {
+            return Err("Not a simulation recording".to_string());
+        }
+
+        let mut version = [0u8; 4];
+        version.copy_from_slice(&blob[MAGIC.len()..MAGIC.len() + 4]);
+        let version = u32::from_le_bytes(version);
+        if version != RECORDING_VERSION {
+            return Err(format!("Recording version {} is not supported (expected {})", version, RECORDING_VERSION));
+        }
+
+        bincode::deserialize(&blob[MAGIC.len() + 4..]).map_err(|e| format!("Failed to load recording: {}", e))
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use rand::Rng;
+use crate::agent::Point2D;
+use crate::clock;
+use crate::pathfinding::{advance_along_path, PathFinder};
+use crate::traffic::CongestionField;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum DispatchStrategy {
+    #[default]
+    NearestIdle,
+    Batch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideHailConfig {
+    pub fleet_size: u32,
+    pub vehicle_speed_kmh: f32,
+    pub strategy: DispatchStrategy,
+    pub batch_interval_hours: f32,  // Only used by batch assignment
+}
+
+impl Default for RideHailConfig {
+    fn default() -> Self {
+        Self {
+            fleet_size: 10,
+            vehicle_speed_kmh: 30.0,
+            strategy: DispatchStrategy::NearestIdle,
+            batch_interval_hours: 0.05,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideRequest {
+    pub agent_id: u32,
+    pub pickup: Point2D,
+    pub dropoff: Point2D,
+    pub requested_at: f64, // Clock seconds
+    pub vehicle: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VehicleState {
+    Idle,
+    EnRouteToPickup,
+    Occupied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideHailVehicle {
+    pub id: u32,
+    pub position: Point2D,
+    pub state: VehicleState,
+    pub passenger: Option<u32>,
+    pub path: Vec<Point2D>,
+    pub path_progress: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RideHailMetrics {
+    pub requests: u32,
+    pub pickups: u32,
+    pub completed: u32,
+    pub mean_wait_time: f32,  // Hours from request to pickup
+    pub max_wait_time: f32,
+    pub deadhead_km: f32,     // Driven empty towards pickups
+    pub occupied_km: f32,
+    pub deadhead_ratio: f32,
+}
+
+/// Changes the world has to apply to the riders.
+#[derive(Debug, Clone)]
+pub enum RideEvent {
+    PickedUp { agent_id: u32 },
+    Riding { agent_id: u32, position: Point2D },
+    DroppedOff { agent_id: u32, position: Point2D },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RideHailService {
+    pub config: RideHailConfig,
+    pub vehicles: Vec<RideHailVehicle>,
+    pub requests: Vec<RideRequest>,
+    pub metrics: RideHailMetrics,
+    batch_timer: f32, // Seconds since the last batch
+}
+
+impl RideHailService {
+    /// Places the fleet on random road nodes (or POIs when there are no roads).
+    pub fn deploy_fleet(&mut self, anchors: &[Point2D], rng: &mut impl Rng) {
+        self.vehicles.clear();
+        self.requests.clear();
+        if anchors.is_empty() {
+            return;
+        }
+
+        for id in 0..self.config.fleet_size {
+            self.vehicles.push(RideHailVehicle {
+                id,
+                position: anchors[rng.gen_range(0..anchors.len())].clone(),
+                state: VehicleState::Idle,
+                passenger: None,
+                path: Vec::new(),
+                path_progress: 0.0,
+            });
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.vehicles.is_empty()
+    }
+
+    pub fn has_request(&self, agent_id: u32) -> bool {
+        self.requests.iter().any(|r| r.agent_id == agent_id)
+    }
+
+    pub fn request(&mut self, agent_id: u32, pickup: Point2D, dropoff: Point2D, now: f64) {
+        self.metrics.requests += 1;
+        self.requests.push(RideRequest {
+            agent_id,
+            pickup,
+            dropoff,
+            requested_at: now,
+            vehicle: None,
+        });
+    }
+
+    /// Advances the fleet by `dt` simulated seconds to `now`, in clock seconds.
+    pub fn update(&mut self, dt: f32, now: f64, pathfinder: &PathFinder, congestion: &CongestionField) -> Vec<RideEvent> {
+        match self.config.strategy {
+            DispatchStrategy::NearestIdle => self.dispatch(pathfinder),
+            DispatchStrategy::Batch => {
+                self.batch_timer += dt;
+                if self.batch_timer >= self.config.batch_interval_hours * clock::SECONDS_PER_HOUR {
+                    self.batch_timer = 0.0;
+                    self.dispatch(pathfinder);
+                }
+            }
+        }
+
+        self.move_fleet(dt, now, pathfinder, congestion)
+    }
+
+    /// Nearest-idle dispatch serves requests first come first served. Batch dispatch
+    /// collects every open request and idle vehicle, then greedily matches the closest pairs.
+    fn dispatch(&mut self, pathfinder: &PathFinder) {
+        let open: Vec<usize> = (0..self.requests.len())
+            .filter(|&i| self.requests[i].vehicle.is_none())
+            .collect();
+        let idle: Vec<usize> = (0..self.vehicles.len())
+            .filter(|&v| matches!(self.vehicles[v].state, VehicleState::Idle))
+            .collect();
+        if open.is_empty() || idle.is_empty() {
+            return;
+        }
+
+        let mut matches = Vec::new();
+        match self.config.strategy {
+            DispatchStrategy::NearestIdle => {
+                let mut free = idle;
+                for r in open {
+                    let pickup = &self.requests[r].pickup;
+                    let nearest = free.iter().enumerate().min_by(|(_, &a), (_, &b)| {
+                        let da = self.vehicles[a].position.distance_to(pickup);
+                        let db = self.vehicles[b].position.distance_to(pickup);
+                        da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    let Some((slot, &v)) = nearest else { break };
+                    free.swap_remove(slot);
+                    matches.push((r, v));
+                }
+            }
+            DispatchStrategy::Batch => {
+                let mut pairs: Vec<(f32, usize, usize)> = open.iter()
+                    .flat_map(|&r| idle.iter().map(move |&v| (r, v)))
+                    .map(|(r, v)| (self.vehicles[v].position.distance_to(&self.requests[r].pickup), r, v))
+                    .collect();
+                pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+                let mut used_requests = Vec::new();
+                let mut used_vehicles = Vec::new();
+                for (_, r, v) in pairs {
+                    if used_requests.contains(&r) || used_vehicles.contains(&v) {
+                        continue;
+                    }
+                    used_requests.push(r);
+                    used_vehicles.push(v);
+                    matches.push((r, v));
+                }
+            }
+        }
+
+        for (r, v) in matches {
+            let vehicle = &mut self.vehicles[v];
+            let request = &mut self.requests[r];
+            vehicle.state = VehicleState::EnRouteToPickup;
+            vehicle.passenger = Some(request.agent_id);
+            vehicle.path = pathfinder.find_path(&vehicle.position, &request.pickup);
+            vehicle.path_progress = 0.0;
+            request.vehicle = Some(vehicle.id);
+        }
+    }
+
+    fn move_fleet(&mut self, dt: f32, now: f64, pathfinder: &PathFinder, congestion: &CongestionField) -> Vec<RideEvent> {
+        let mut events = Vec::new();
+
+        for vehicle in &mut self.vehicles {
+            let Some(agent_id) = vehicle.passenger else { continue };
+
+            let before = vehicle.position.clone();
+            let distance = clock::distance_m(self.config.vehicle_speed_kmh * congestion.speed_factor(&vehicle.position), dt);
+            advance_along_path(&mut vehicle.path, &mut vehicle.path_progress, &mut vehicle.position, distance);
+            let moved_km = before.distance_to(&vehicle.position) / 1000.0;
+
+            match vehicle.state {
+                VehicleState::EnRouteToPickup => {
+                    self.metrics.deadhead_km += moved_km;
+                    if vehicle.path.len() > 1 {
+                        continue;
+                    }
+
+                    let Some(request) = self.requests.iter().find(|r| r.agent_id == agent_id) else {
+                        // Passenger gave up, go back to idling
+                        vehicle.state = VehicleState::Idle;
+                        vehicle.passenger = None;
+                        continue;
+                    };
+
+                    let wait = clock::hours((now - request.requested_at) as f32);
+                    self.metrics.pickups += 1;
+                    self.metrics.mean_wait_time += (wait - self.metrics.mean_wait_time) / self.metrics.pickups as f32;
+                    self.metrics.max_wait_time = self.metrics.max_wait_time.max(wait);
+
+                    vehicle.state = VehicleState::Occupied;
+                    vehicle.path = pathfinder.find_path(&vehicle.position, &request.dropoff);
+                    vehicle.path_progress = 0.0;
+                    events.push(RideEvent::PickedUp { agent_id });
+                }
+                VehicleState::Occupied => {
+                    self.metrics.occupied_km += moved_km;
+                    if vehicle.path.len() > 1 {
+                        events.push(RideEvent::Riding { agent_id, position: vehicle.position.clone() });
+                        continue;
+                    }
+
+                    vehicle.state = VehicleState::Idle;
+                    vehicle.passenger = None;
+                    vehicle.path.clear();
+                    self.requests.retain(|r| r.agent_id != agent_id);
+                    self.metrics.completed += 1;
+                    events.push(RideEvent::DroppedOff { agent_id, position: vehicle.position.clone() });
+                }
+                VehicleState::Idle => {}
+            }
+        }
+
+        let driven = self.metrics.deadhead_km + self.metrics.occupied_km;
+        self.metrics.deadhead_ratio = if driven > 0.0 { self.metrics.deadhead_km / driven } else { 0.0 };
+
+        events
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use rand::Rng;
+use crate::agent::ScheduleEntry;
+
+/// How a start time or duration is drawn, in hours.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeDistribution {
+    Fixed { value: f32 },
+    Uniform { min: f32, max: f32 },
+    Normal { mean: f32, std_dev: f32 }, // Clamped to [0, 24]
+}
+
+impl TimeDistribution {
+    pub fn sample(&self, rng: &mut impl Rng) -> f32 {
+        match *self {
+            TimeDistribution::Fixed { value } => value,
+            TimeDistribution::Uniform { min, max } => min + rng.gen::<f32>() * (max - min),
+            TimeDistribution::Normal { mean, std_dev } => {
+                // Box-Muller
+                let u1 = rng.gen::<f32>().max(f32::MIN_POSITIVE);
+                let u2 = rng.gen::<f32>();
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+                (mean + z * std_dev).clamp(0.0, 24.0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityTemplate {
+    pub name: String,
+    pub poi_type: u32,
+    pub probability: f32,             // Chance the activity is part of a given day
+    pub start_time: TimeDistribution,
+    pub duration: TimeDistribution,
+    #[serde(default)]
+    pub after: Vec<String>,           // Starts no earlier than the end of these, when they happen
+}
+
+/// The daily activity pattern agents draw their schedules from. Ships with the city config
+/// so scenarios can change behaviour without rebuilding the simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleTemplate {
+    pub activities: Vec<ActivityTemplate>,
+}
+
+impl Default for ScheduleTemplate {
+    fn default() -> Self {
+        let activity = |name: &str, poi_type, probability, start: f32, duration| ActivityTemplate {
+            name: name.to_string(),
+            poi_type,
+            probability,
+            start_time: TimeDistribution::Uniform { min: start, max: start + 2.0 },
+            duration: TimeDistribution::Fixed { value: duration },
+            after: Vec::new(),
+        };
+
+        Self {
+            activities: vec![
+                activity("work", 1, 1.0, 8.0, 8.0),      // OFFICE
+                activity("lunch", 3, 0.6, 12.0, 1.0),    // RESTAURANT
+                activity("shopping", 2, 0.4, 17.0, 1.5), // SHOP
+                activity("park", 6, 0.3, 19.0, 2.0),     // PARK_POI
+                activity("home", 0, 1.0, 21.0, 10.0),    // HOME
+            ],
+        }
+    }
+}
+
+impl ScheduleTemplate {
+    /// Draws one day's schedule. Activities are considered in template order and the
+    /// result is sorted by start time.
+    pub fn generate(&self, rng: &mut impl Rng) -> Vec<ScheduleEntry> {
+        let mut schedule: Vec<ScheduleEntry> = Vec::new();
+        let mut ends: Vec<(&str, f32)> = Vec::new();
+
+        for activity in &self.activities {
+            // Certain activities skip the draw so the random stream only moves when it matters
+            if activity.probability < 1.0 && rng.gen::<f32>() >= activity.probability {
+                continue;
+            }
+
+            let earliest = ends.iter()
+                .filter(|(name, _)| activity.after.iter().any(|a| a == name))
+                .map(|(_, end)| *end)
+                .fold(0.0, f32::max);
+            let start_time = activity.start_time.sample(rng).max(earliest);
+            let duration = activity.duration.sample(rng).max(0.0);
+
+            ends.push((&activity.name, start_time + duration));
+            schedule.push(ScheduleEntry {
+                poi_type: activity.poi_type,
+                start_time,
+                duration,
+                preferred_poi_id: None,
+            });
+        }
+
+        schedule.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap_or(std::cmp::Ordering::Equal));
+        schedule
+    }
+}
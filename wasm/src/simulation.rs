@@ -1,11 +1,23 @@
 use serde::{Deserialize, Serialize};
+use crate::agent_store::AgentStore;
+use crate::delta::{AgentDelta, ChangeTracker};
+use crate::culling::{AgentsInView, SpatialIndex, View};
+use crate::meso::{AreaOfInterest, LodStats};
+use crate::live_scaling::{LiveScaling, ScalingEvent};
+use crate::performance::PerformanceProfile;
+use crate::state_hash::{HashLog, StateHash};
 use crate::world::{World, CityModel};
 use crate::traffic::TrafficData;
+#[cfg(feature = "networking")]
+use crate::replay::{InputEvent, Recording, Trajectory};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulationConfig {
     pub speed_multiplier: f32,
     pub max_agents: u32,
+    pub fixed_step_ms: f64,  // Real time covered by one simulation step
+    pub max_substeps: u32,   // Steps per tick before the backlog is dropped
 }
 
 impl Default for SimulationConfig {
@@ -13,21 +25,49 @@ impl Default for SimulationConfig {
         Self {
             speed_multiplier: 1.0,
             max_agents: 1000,
+            fixed_step_ms: 16.0,
+            max_substeps: 5,
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Simulation {
     pub world: World,
     pub running: bool,
     pub speed_multiplier: f32,
     pub config: SimulationConfig,
     pub seed: u64,
+    accumulator_ms: f64,
+    interpolation_alpha: f32,
+    #[serde(skip)]
+    agent_store: AgentStore,
+    #[serde(skip)]
+    changes: Option<ChangeTracker>, // Started by the first delta request
+    profile: PerformanceProfile,
+    #[serde(skip)]
+    spatial_index: SpatialIndex,
+    // Reacts to the frame rate of the machine it runs on, so a loaded snapshot starts without
+    // it; the profile it last applied is kept
+    #[serde(skip)]
+    scaling: Option<LiveScaling>,
+    // Steps are counted from when the log was started, so a loaded snapshot starts without one
+    #[serde(skip)]
+    hash_log: Option<HashLog>,
+    #[cfg(feature = "networking")]
+    #[serde(skip)]
+    recording: Option<Recording>,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Simulation {
     pub fn new() -> Self {
-        Self::new_with_seed(js_sys::Date::now() as u64)
+        Self::new_with_seed(crate::platform::now_ms() as u64)
     }
 
     pub fn new_with_seed(seed: u64) -> Self {
@@ -37,6 +77,16 @@ impl Simulation {
             speed_multiplier: 1.0,
             config: SimulationConfig::default(),
             seed,
+            accumulator_ms: 0.0,
+            interpolation_alpha: 0.0,
+            agent_store: AgentStore::default(),
+            changes: None,
+            profile: PerformanceProfile::default(),
+            spatial_index: SpatialIndex::default(),
+            scaling: None,
+            hash_log: None,
+            #[cfg(feature = "networking")]
+            recording: None,
         }
     }
 
@@ -48,6 +98,7 @@ impl Simulation {
         self.config = config;
         self.speed_multiplier = self.config.speed_multiplier;
         self.world.load_city_with_seed(city_data, self.seed);
+        self.spatial_index.invalidate();
         self.running = false;
     }
 
@@ -56,40 +107,387 @@ impl Simulation {
         self.init_with_config(city_data, SimulationConfig::default());
     }
 
+    #[cfg(feature = "scripting")]
+    pub fn load_plans(&mut self, plans: &crate::plans::PlanSet) -> Result<(), String> {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::LoadPlans(plans.clone()));
+        self.spatial_index.invalidate();
+        self.world.load_plans(plans)
+    }
+
+    /// Snapshots the current state and logs every input from here on. With an interval,
+    /// agent positions are also sampled for playback without re-simulating.
+    #[cfg(feature = "networking")]
+    pub fn start_recording(&mut self, trajectory_interval_seconds: Option<f64>) -> Result<(), String> {
+        self.recording = None;
+        let mut trajectory = trajectory_interval_seconds.map(Trajectory::new);
+        if let Some(trajectory) = &mut trajectory {
+            trajectory.capture(self.world.clock.elapsed_seconds, &self.world.agents);
+        }
+        self.recording = Some(Recording {
+            initial_state: self.save_state()?,
+            inputs: Vec::new(),
+            trajectory,
+        });
+        Ok(())
+    }
+
+    #[cfg(feature = "networking")]
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    /// Rebuilds a run from its recording, stopping after `ticks` ticks if given.
+    #[cfg(feature = "networking")]
+    pub fn replay(recording: &Recording, ticks: Option<usize>) -> Result<Self, String> {
+        let mut simulation = Self::load_state(&recording.initial_state)?;
+        let mut ticked = 0;
+
+        for input in &recording.inputs {
+            // Inputs after the last tick wanted belong to the ticks that were cut off
+            if ticks.is_some_and(|limit| ticked >= limit) {
+                break;
+            }
+            if let InputEvent::Tick { .. } = input {
+                ticked += 1;
+            }
+            simulation.apply_input(input)?;
+        }
+        Ok(simulation)
+    }
+
+    #[cfg(feature = "networking")]
+    fn apply_input(&mut self, input: &InputEvent) -> Result<(), String> {
+        match input {
+            InputEvent::Start => self.start(),
+            InputEvent::Pause => self.pause(),
+            InputEvent::SetSpeed(multiplier) => self.set_speed(*multiplier),
+            InputEvent::SetTimeScale(scale) => self.set_time_scale(*scale),
+            InputEvent::Tick { elapsed_ms: Some(elapsed_ms) } => {
+                self.tick_elapsed(*elapsed_ms);
+            }
+            InputEvent::Tick { elapsed_ms: None } => self.tick(),
+            InputEvent::FastForward(hours) => {
+                self.fast_forward(*hours);
+            }
+            InputEvent::SetAreaOfInterest(area) => self.set_area_of_interest(*area),
+            InputEvent::SetPopulationCap(cap) => self.set_population_cap(*cap),
+            InputEvent::SetUpdateFrequency(hz) => self.set_update_frequency(*hz),
+            #[cfg(feature = "scripting")]
+            InputEvent::LoadPlans(plans) => self.load_plans(plans)?,
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "networking")]
+    fn record(&mut self, input: InputEvent) {
+        if let Some(recording) = &mut self.recording {
+            recording.inputs.push(input);
+        }
+    }
+
+    /// Advances exactly one fixed step, for callers that do not track frame time.
     pub fn tick(&mut self) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::Tick { elapsed_ms: None });
         if self.running {
-            let dt = 0.016 * self.speed_multiplier; // ~60 FPS
-            self.world.update(dt);
+            self.step();
+        }
+    }
+
+    /// Advances by however many fixed steps fit in the elapsed wall time, carrying the
+    /// remainder over to the next call. Returns the number of steps taken.
+    pub fn tick_elapsed(&mut self, elapsed_ms: f64) -> u32 {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::Tick { elapsed_ms: Some(elapsed_ms) });
+        if !self.running {
+            return 0;
+        }
+
+        let step_ms = self.config.fixed_step_ms.max(1.0);
+        if elapsed_ms.is_finite() && elapsed_ms > 0.0 {
+            self.accumulator_ms += elapsed_ms;
+        }
+
+        let mut steps = 0;
+        while self.accumulator_ms >= step_ms && steps < self.config.max_substeps {
+            self.step();
+            self.accumulator_ms -= step_ms;
+            steps += 1;
+        }
+
+        // Fell too far behind (tab in background, slow device): drop the backlog
+        // instead of trying to catch up and falling further behind
+        if self.accumulator_ms >= step_ms {
+            self.accumulator_ms %= step_ms;
+        }
+
+        self.interpolation_alpha = (self.accumulator_ms / step_ms) as f32;
+        steps
+    }
+
+    fn step(&mut self) {
+        let real_seconds = self.config.fixed_step_ms / 1000.0 * self.speed_multiplier as f64;
+        let dt = self.world.clock.scale(real_seconds);
+        self.advance(dt);
+    }
+
+    fn advance(&mut self, dt: f32) {
+        self.world.update(dt);
+        let dirty = self.world.take_dirty();
+        if let Some(changes) = &mut self.changes {
+            changes.observe(&dirty, &self.world.agents, &self.world.agent_lookup);
+        }
+        #[cfg(feature = "networking")]
+        if let Some(trajectory) = self.recording.as_mut().and_then(|r| r.trajectory.as_mut()) {
+            trajectory.capture(self.world.clock.elapsed_seconds, &self.world.agents);
+        }
+        if let Some(mut log) = self.hash_log.take() {
+            log.step(self);
+            self.hash_log = Some(log);
         }
     }
 
+    /// Runs `hours` of simulated time in fixed steps as fast as possible, running or not.
+    /// Idle agents sleep in the event queue, so quiet stretches cost little.
+    pub fn fast_forward(&mut self, hours: f32) -> u32 {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::FastForward(hours));
+        let dt = self.world.clock.scale(self.config.fixed_step_ms / 1000.0);
+        if dt <= 0.0 {
+            return 0;
+        }
+
+        let target = self.world.clock.elapsed_seconds + (hours.max(0.0) * crate::clock::SECONDS_PER_HOUR) as f64;
+        let mut steps = 0;
+        while self.world.clock.elapsed_seconds < target {
+            let remaining = (target - self.world.clock.elapsed_seconds) as f32;
+            self.advance(dt.min(remaining));
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Fingerprint of the current state; equal hashes mean runs have not diverged.
+    pub fn state_hash(&self) -> Result<StateHash, String> {
+        crate::state_hash::hash_state(self)
+    }
+
+    /// Hashes the state every `interval` steps from now on, replacing any earlier log.
+    pub fn start_hash_log(&mut self, interval: u64) {
+        self.hash_log = Some(HashLog::new(interval));
+    }
+
+    pub fn stop_hash_log(&mut self) -> Option<HashLog> {
+        self.hash_log.take()
+    }
+
+    pub fn get_hash_log(&self) -> Option<&HashLog> {
+        self.hash_log.as_ref()
+    }
+
+    pub fn get_scheduler_stats(&self) -> crate::events::SchedulerStats {
+        self.world.events.stats(self.world.agents.len())
+    }
+
+    /// How far the renderer is between the last step and the next, in [0, 1).
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
     pub fn start(&mut self) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::Start);
         self.running = true;
     }
 
     pub fn pause(&mut self) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::Pause);
         self.running = false;
     }
 
     pub fn set_speed(&mut self, multiplier: f32) {
-        self.speed_multiplier = multiplier.max(0.1).min(10.0);
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::SetSpeed(multiplier));
+        self.speed_multiplier = multiplier.clamp(0.1, 10.0);
         self.config.speed_multiplier = self.speed_multiplier;
     }
 
+    /// Simulated seconds per real second, before the speed multiplier.
+    pub fn set_time_scale(&mut self, seconds_per_real_second: f64) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::SetTimeScale(seconds_per_real_second));
+        self.world.clock.seconds_per_real_second = seconds_per_real_second.max(0.0);
+    }
+
+    /// Agents inside `area` are simulated in full and the rest link by link; `None` simulates
+    /// everyone in full.
+    pub fn set_area_of_interest(&mut self, area: Option<AreaOfInterest>) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::SetAreaOfInterest(area));
+        self.world.set_area_of_interest(area);
+        // Agents taken out of the queue model are placed along their link
+        self.spatial_index.invalidate();
+    }
+
+    pub fn get_lod_stats(&self) -> LodStats {
+        self.world.lod_stats()
+    }
+
+    pub fn get_datetime(&self) -> crate::clock::SimDateTime {
+        self.world.clock.datetime()
+    }
+
+    #[cfg(feature = "networking")]
+    pub fn save_state(&self) -> Result<Vec<u8>, String> {
+        crate::snapshot::save(self)
+    }
+
+    /// Restores a `save_state` snapshot, performance profile included. Adaptive scaling and the
+    /// hash log are not part of it; enable or start them again if the restored run needs them.
+    #[cfg(feature = "networking")]
+    pub fn load_state(blob: &[u8]) -> Result<Self, String> {
+        crate::snapshot::load(blob)
+    }
+
     pub fn get_agent_states(&self) -> Vec<&crate::agent::Agent> {
         self.world.agents.iter().collect()
     }
 
+    /// Brings the compact agent arrays up to date and returns them.
+    pub fn sync_agent_store(&mut self) -> &AgentStore {
+        self.agent_store.sync(&self.world.agents);
+        &self.agent_store
+    }
+
+    /// Agents that moved, changed state, appeared or disappeared after `since`, a frame
+    /// number from an earlier delta (0 for everything).
+    pub fn get_changes_since(&mut self, since: u32) -> AgentDelta {
+        if self.changes.is_none() {
+            // The tracker starts out knowing nobody
+            self.world.mark_all_dirty();
+        }
+        let changes = self.changes.get_or_insert_with(ChangeTracker::default);
+        // Also picks up changes made outside a step, e.g. imported plans
+        changes.observe(&self.world.take_dirty(), &self.world.agents, &self.world.agent_lookup);
+        changes.changes_since(since, &self.world.agents, &self.world.agent_lookup)
+    }
+
+    /// Visible agents for the renderer, culled and simplified per the performance profile.
+    pub fn get_agents_in_view(&mut self, view: &View, radius: Option<f32>, lod: u32) -> AgentsInView {
+        self.spatial_index.update(&self.world.agents, self.world.clock.elapsed_seconds);
+        crate::culling::agents_in_view(&self.spatial_index, &self.world.agents, view, radius, lod, &self.profile)
+    }
+
+    pub fn set_performance_profile(&mut self, profile: PerformanceProfile) {
+        self.profile = profile;
+    }
+
+    pub fn get_performance_profile(&self) -> &PerformanceProfile {
+        &self.profile
+    }
+
+    /// Lets frame rate reports drive the agent count, tick rate and render distance, starting
+    /// from `profile`, which is applied straight away.
+    pub fn enable_adaptive_scaling(&mut self, profile: PerformanceProfile) {
+        self.scaling = Some(LiveScaling::new(profile.clone()));
+        self.apply_profile(profile);
+    }
+
+    /// Stops adjusting; whatever was applied last stays in effect.
+    pub fn disable_adaptive_scaling(&mut self) {
+        self.scaling = None;
+    }
+
+    /// Reports one rendered frame to the adaptive scaler. Returns true if it led to an
+    /// adjustment, which is also added to the scaling log.
+    pub fn report_frame(&mut self, fps: f64, frame_ms: f64) -> bool {
+        let Some(scaling) = &mut self.scaling else { return false };
+        let Some(profile) = scaling.observe_frame(fps, frame_ms) else { return false };
+
+        scaling.record(ScalingEvent {
+            sim_seconds: self.world.clock.elapsed_seconds,
+            fps,
+            max_agents: (self.profile.max_agents(), profile.max_agents()),
+            update_frequency: (self.profile.update_frequency(), profile.update_frequency()),
+            render_distance: (self.profile.render_distance(), profile.render_distance()),
+        });
+        self.apply_profile(profile);
+        true
+    }
+
+    pub fn get_scaling_log(&self) -> &[ScalingEvent] {
+        self.scaling.as_ref().map_or(&[], |scaling| scaling.log())
+    }
+
+    fn apply_profile(&mut self, profile: PerformanceProfile) {
+        self.set_population_cap(Some(profile.max_agents()));
+        self.set_update_frequency(profile.update_frequency());
+        self.profile = profile;
+    }
+
+    /// `cap` agents are simulated: any more despawn once idle and come back when the cap is
+    /// raised or lifted, and a cap above everyone there was spawns newcomers.
+    pub fn set_population_cap(&mut self, cap: Option<u32>) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::SetPopulationCap(cap));
+        self.world.set_population_cap(cap.map(|cap| cap as usize));
+        self.spatial_index.invalidate();
+    }
+
+    /// Simulation steps per second of wall time. Simulated time per wall second is unchanged,
+    /// each step just covers more or less of it.
+    pub fn set_update_frequency(&mut self, hz: u32) {
+        #[cfg(feature = "networking")]
+        self.record(InputEvent::SetUpdateFrequency(hz));
+        self.config.fixed_step_ms = 1000.0 / hz.max(1) as f64;
+    }
+
+    /// The compact arrays as of the last `sync_agent_store`.
+    pub fn get_agent_store(&self) -> &AgentStore {
+        &self.agent_store
+    }
+
     pub fn get_traffic_data(&self) -> TrafficData {
-        TrafficData::from_agents(&self.world.agents, &self.world.city.roads)
+        TrafficData::from_agents(&self.world.agents, &self.world.city.roads, &self.world.mode_split)
+    }
+
+    pub fn get_learning_convergence(&self) -> &[crate::learning::DayConvergence] {
+        &self.world.convergence.history
+    }
+
+    pub fn get_emergency_state(&self) -> &crate::emergency::EmergencyService {
+        &self.world.emergency
+    }
+
+    #[cfg(feature = "economics")]
+    pub fn get_ride_hail_state(&self) -> &crate::ride_hailing::RideHailService {
+        &self.world.ride_hail
+    }
+
+    #[cfg(feature = "advanced-ai")]
+    pub fn get_autonomous_state(&self) -> &crate::autonomous::AutonomousFleet {
+        &self.world.autonomous
+    }
+
+    #[cfg(feature = "economics")]
+    pub fn get_charging_state(&self) -> &crate::charging::ChargingNetwork {
+        &self.world.charging
+    }
+
+    #[cfg(feature = "physics")]
+    pub fn get_drone_states(&self) -> Vec<crate::drone::DroneState> {
+        self.world.drones.states()
     }
 
     pub fn is_running(&self) -> bool {
         self.running
     }
 
+    /// Hour of day, for display; the simulation itself keeps time in clock seconds.
     pub fn get_time(&self) -> f32 {
-        self.world.time
+        self.world.clock.hour_of_day()
     }
 
     pub fn get_agent_count(&self) -> u32 {
@@ -0,0 +1,48 @@
+use crate::features;
+use crate::simulation::Simulation;
+
+const MAGIC: &[u8; 4] = b"USIM";
+
+/// Bump whenever a serialized type changes shape; older blobs are rejected rather than misread.
+pub const SNAPSHOT_VERSION: u32 = 13;
+
+const HEADER_LEN: usize = MAGIC.len() + 8;
+
+/// Snapshot layout: magic, little-endian format version, the build's feature mask, then the
+/// bincode-encoded simulation.
+/// bincode keeps floats and the RNG state bit for bit, so a restored run continues exactly
+/// where the saved one would have.
+pub fn save(simulation: &Simulation) -> Result<Vec<u8>, String> {
+    let body = bincode::serialize(simulation).map_err(|e| format!("Failed to save snapshot: {}", e))?;
+
+    let mut blob = Vec::with_capacity(HEADER_LEN + body.len());
+    blob.extend_from_slice(MAGIC);
+    blob.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    blob.extend_from_slice(&features::mask().to_le_bytes());
+    blob.extend_from_slice(&body);
+    Ok(blob)
+}
+
+pub fn load(blob: &[u8]) -> Result<Simulation, String> {
+    if blob.len() < HEADER_LEN || &blob[..MAGIC.len()] != MAGIC {
+        return Err("Not a simulation snapshot".to_string());
+    }
+
+    let word = |at: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&blob[at..at + 4]);
+        u32::from_le_bytes(bytes)
+    };
+
+    let version = word(MAGIC.len());
+    if version != SNAPSHOT_VERSION {
+        return Err(format!("Snapshot version {} is not supported (expected {})", version, SNAPSHOT_VERSION));
+    }
+
+    let mask = word(MAGIC.len() + 4);
+    if mask != features::mask() {
+        return Err(format!("Snapshot was saved by a build with features {:#b}, this build has {:#b}", mask, features::mask()));
+    }
+
+    bincode::deserialize(&blob[HEADER_LEN..]).map_err(|e| format!("Failed to load snapshot: {}", e))
+}
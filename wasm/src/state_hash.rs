@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use crate::simulation::{Simulation, SimulationConfig};
+use crate::world::World;
+
+/// Fingerprint of the simulation state, as 16 hex digits per part. `total` covers the world
+/// and the settings that turn steps into simulated time; the parts narrow down where two runs
+/// went apart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHash {
+    pub total: String,
+    pub agents: String,
+    pub clock: String,
+    pub rng: String,
+    pub occupancy: String, // Agents at each POI
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashLogEntry {
+    pub step: u64,        // Steps since the log was started
+    pub sim_seconds: f64,
+    pub hash: String,     // StateHash::total
+}
+
+/// Total hash every `interval` steps, to diff against another build, machine or replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashLog {
+    pub interval: u64,
+    pub steps: u64,
+    pub entries: Vec<HashLogEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>, // Why hashing stopped early, if it did
+}
+
+impl HashLog {
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            steps: 0,
+            entries: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Called after every step. A state that cannot be hashed ends the log, not the run.
+    pub fn step(&mut self, simulation: &Simulation) {
+        if self.error.is_some() {
+            return;
+        }
+        self.steps += 1;
+        if self.steps.is_multiple_of(self.interval) {
+            match fnv(&Logical::of(simulation)) {
+                Ok(hash) => self.entries.push(HashLogEntry {
+                    step: self.steps,
+                    sim_seconds: simulation.world.clock.elapsed_seconds,
+                    hash: hex(hash),
+                }),
+                Err(message) => self.error = Some(message),
+            }
+        }
+    }
+}
+
+/// What a run's outcome depends on. The tick accumulator, interpolation alpha and performance
+/// profile follow the wall clock and the device, so they are left out.
+#[derive(Serialize)]
+struct Logical<'a> {
+    world: &'a World,
+    seed: u64,
+    speed_multiplier: f32,
+    config: &'a SimulationConfig,
+}
+
+impl<'a> Logical<'a> {
+    fn of(simulation: &'a Simulation) -> Self {
+        Self {
+            world: &simulation.world,
+            seed: simulation.seed,
+            speed_multiplier: simulation.speed_multiplier,
+            config: &simulation.config,
+        }
+    }
+}
+
+/// Hashes go through the bincode encoding snapshots use: floats and the RNG state bit for bit,
+/// lengths and `usize` as fixed-width u64, and maps in key order (all simulation state uses
+/// `BTreeMap`), so the same state gives the same hash on any platform, 32 or 64 bit.
+pub fn hash_state(simulation: &Simulation) -> Result<StateHash, String> {
+    let world = &simulation.world;
+    let mut occupancy: BTreeMap<&str, u32> = BTreeMap::new();
+    for poi in world.agents.iter().filter_map(|agent| agent.current_poi.as_deref()) {
+        *occupancy.entry(poi).or_insert(0) += 1;
+    }
+
+    Ok(StateHash {
+        total: hex(fnv(&Logical::of(simulation))?),
+        agents: hex(fnv(&world.agents)?),
+        clock: hex(fnv(&world.clock)?),
+        rng: hex(fnv(&world.rng)?),
+        occupancy: hex(fnv(&occupancy)?),
+    })
+}
+
+fn hex(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+fn fnv(value: &impl Serialize) -> Result<u64, String> {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    // Writing into the hasher cannot fail, so an error means part of the state has no encoding
+    // and would silently drop out of the hash
+    bincode::serialize_into(&mut hasher, value).map_err(|e| format!("Failed to hash the simulation state: {}", e))?;
+    Ok(hasher.0)
+}
+
+/// 64-bit FNV-1a, fed straight from the serializer so nothing is buffered.
+struct Fnv1a(u64);
+
+impl io::Write for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
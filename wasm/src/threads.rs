@@ -0,0 +1,82 @@
+//! Rayon worker threads in the browser, for bundles built with the `parallel` feature and
+//! atomics (`npm run build:wasm:threads`). `init_thread_pool` starts one Web Worker per
+//! thread on this module's shared memory (see js/workers.js); each picks up a rayon thread
+//! from the channel and runs it until the pool shuts down. The page has to be cross-origin
+//! isolated for shared memory, and the pool is only used off the main thread, which may not
+//! block waiting for the workers (see `platform::can_use_threads`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use wasm_bindgen::prelude::*;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[wasm_bindgen(module = "/js/workers.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = startWorkers)]
+    fn start_workers(module: JsValue, memory: JsValue, builder: PoolBuilder) -> js_sys::Promise;
+}
+
+#[wasm_bindgen]
+pub struct PoolBuilder {
+    num_threads: usize,
+    sender: Sender<rayon::ThreadBuilder>,
+    receiver: &'static Mutex<Receiver<rayon::ThreadBuilder>>, // Leaked, workers may still be waiting on it once the builder is gone
+}
+
+#[wasm_bindgen]
+impl PoolBuilder {
+    fn new(num_threads: usize) -> Self {
+        let (sender, receiver) = channel();
+        Self { num_threads, sender, receiver: Box::leak(Box::new(Mutex::new(receiver))) }
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Handed to each worker so it can take its thread from the channel.
+    pub fn receiver(&self) -> *const Mutex<Receiver<rayon::ThreadBuilder>> {
+        self.receiver
+    }
+
+    /// Called once every worker is waiting on the receiver.
+    pub fn build(&mut self) -> Result<(), JsValue> {
+        let sender = self.sender.clone();
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(self.num_threads)
+            .spawn_handler(move |thread| {
+                sender.send(thread).map_err(|_| std::io::Error::other("thread pool workers are gone"))
+            })
+            .build_global()
+            .map_err(|e| JsValue::from_str(&format!("Failed to start the thread pool: {}", e)))?;
+        RUNNING.store(true, Ordering::Release);
+        Ok(())
+    }
+}
+
+/// Starts `num_threads` workers for rayon. Resolves once the pool is ready.
+#[wasm_bindgen]
+pub fn init_thread_pool(num_threads: usize) -> js_sys::Promise {
+    start_workers(wasm_bindgen::module(), wasm_bindgen::memory(), PoolBuilder::new(num_threads))
+}
+
+/// Entry point of each worker; blocks until the pool hands it a thread, then runs it.
+#[wasm_bindgen]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn run_pool_worker(receiver: *const Mutex<Receiver<rayon::ThreadBuilder>>) -> Result<(), JsValue> {
+    // Safety: comes from `PoolBuilder::receiver`, which is never freed
+    let receiver = unsafe { &*receiver };
+    let thread = receiver.lock()
+        .map_err(|_| JsValue::from_str("thread pool receiver poisoned"))?
+        .recv()
+        .map_err(|_| JsValue::from_str("thread pool closed before this worker got a thread"))?;
+    thread.run();
+    Ok(())
+}
+
+/// Whether `init_thread_pool` has finished.
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Acquire)
+}
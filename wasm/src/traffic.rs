@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use crate::agent::{Agent, Point2D};
+use std::collections::{BTreeMap, HashMap};
+use crate::agent::{Agent, AgentState, AgentType, Point2D};
 use crate::world::Road;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +9,7 @@ pub struct TrafficData {
     pub poi_popularity: HashMap<String, u32>,
     pub flow_matrix: Vec<TrafficFlow>,
     pub congestion_points: Vec<CongestionPoint>,
+    pub mode_split: BTreeMap<AgentType, u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,8 +26,55 @@ pub struct CongestionPoint {
     pub road_id: String,
 }
 
+/// Coarse grid of moving vehicles, used to slow down traffic in busy cells
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CongestionField {
+    pub cell_size: f32,
+    pub cell_capacity: f32,
+    counts: BTreeMap<(i32, i32), u32>,
+}
+
+impl Default for CongestionField {
+    fn default() -> Self {
+        Self {
+            cell_size: 100.0,
+            cell_capacity: 8.0,
+            counts: BTreeMap::new(),
+        }
+    }
+}
+
+impl CongestionField {
+    pub fn is_motorized(agent_type: &AgentType) -> bool {
+        !matches!(agent_type, AgentType::Pedestrian | AgentType::Bicycle)
+    }
+
+    fn cell(&self, position: &Point2D) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Counts moving vehicles among `indices`, which must include every traveling agent.
+    pub fn rebuild(&mut self, agents: &[Agent], indices: impl Iterator<Item = usize>) {
+        self.counts.clear();
+        for agent in indices.map(|index| &agents[index]) {
+            if matches!(agent.state, AgentState::Traveling) && Self::is_motorized(&agent.agent_type) {
+                *self.counts.entry(self.cell(&agent.position)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// BPR style slowdown: 1 / (1 + 0.15 * (volume / capacity)^4)
+    pub fn speed_factor(&self, position: &Point2D) -> f32 {
+        let volume = self.counts.get(&self.cell(position)).copied().unwrap_or(0) as f32;
+        1.0 / (1.0 + 0.15 * (volume / self.cell_capacity).powi(4))
+    }
+}
+
 impl TrafficData {
-    pub fn from_agents(agents: &[Agent], roads: &[Road]) -> Self {
+    pub fn from_agents(agents: &[Agent], roads: &[Road], mode_split: &BTreeMap<AgentType, u32>) -> Self {
         let mut road_densities = HashMap::new();
         let mut poi_popularity = HashMap::new();
         let flow_matrix = Vec::new();
@@ -65,6 +113,7 @@ impl TrafficData {
             poi_popularity,
             flow_matrix,
             congestion_points,
+            mode_split: mode_split.clone(),
         }
     }
 
@@ -80,7 +129,7 @@ impl TrafficData {
         })
     }
 
-    fn point_to_line_distance(point: &Point2D, line_start: &Point2D, line_end: &Point2D) -> f32 {
+    pub fn point_to_line_distance(point: &Point2D, line_start: &Point2D, line_end: &Point2D) -> f32 {
         let dx = line_end.x - line_start.x;
         let dy = line_end.y - line_start.y;
 
@@ -92,7 +141,7 @@ impl TrafficData {
         }
 
         let t = ((point.x - line_start.x) * dx + (point.y - line_start.y) * dy) / (dx * dx + dy * dy);
-        let t = t.max(0.0).min(1.0);
+        let t = t.clamp(0.0, 1.0);
 
         let closest_x = line_start.x + t * dx;
         let closest_y = line_start.y + t * dy;
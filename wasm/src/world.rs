@@ -1,7 +1,31 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use crate::agent::{Agent, Point2D};
-use rand::SeedableRng;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::agent::{Agent, AgentState, AgentType, PlannedTrip, Point2D, ScheduleEntry};
+use crate::clock::SimClock;
+use crate::emergency::EmergencyService;
+use crate::events::{EventKind, EventQueue};
+use crate::learning::{ConvergenceTracker, LearningConfig, TravelTimeMemory, TripRecord};
+use crate::meso::{AreaOfInterest, LodStats, MesoModel};
+use crate::mode_choice::{ModeChoiceModel, TripContext};
+use crate::pathfinding::PathFinder;
+use crate::schedule::ScheduleTemplate;
+#[cfg(feature = "advanced-ai")]
+use crate::autonomous::AutonomousFleet;
+#[cfg(feature = "economics")]
+use crate::charging::ChargingNetwork;
+#[cfg(feature = "economics")]
+use crate::ride_hailing::{RideEvent, RideHailService};
+#[cfg(feature = "physics")]
+use crate::car_following::CarFollowingModel;
+#[cfg(feature = "physics")]
+use crate::drone::DroneFleet;
+#[cfg(feature = "scripting")]
+use crate::plans::PlanSet;
+use crate::traffic::CongestionField;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CityModel {
@@ -9,6 +33,8 @@ pub struct CityModel {
     pub roads: Vec<Road>,
     pub pois: Vec<POI>,
     pub buildings: Vec<Building>,
+    #[serde(default)]
+    pub schedule_template: ScheduleTemplate,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +56,7 @@ pub struct Road {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct POI {
     pub id: String,
     pub poi_type: u32,
@@ -47,13 +74,46 @@ pub struct Building {
     pub building_type: u32,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct World {
     pub city: CityModel,
     pub agents: Vec<Agent>,
-    pub time: f32,
-    pub day: u32,
-    pub poi_lookup: HashMap<String, usize>,
-    pub zone_lookup: HashMap<String, usize>,
+    pub clock: SimClock,
+    pub events: EventQueue,
+    pub meso: MesoModel,
+    pub day: u32,    // Last day started, to notice the clock rolling over
+    pub poi_lookup: BTreeMap<String, usize>,
+    pub zone_lookup: BTreeMap<String, usize>,
+    pub agent_lookup: BTreeMap<u32, usize>, // Agent id to index in `agents`
+    #[serde(skip)]
+    pub dirty: BTreeSet<u32>, // Ids of agents that may have moved, changed state, spawned or gone since `take_dirty`
+    pub pathfinder: PathFinder,
+    pub mode_choice: ModeChoiceModel,
+    pub mode_split: BTreeMap<AgentType, u32>,
+    pub learning: LearningConfig,
+    pub convergence: ConvergenceTracker,
+    pub congestion: CongestionField,
+    pub emergency: EmergencyService,
+    #[cfg(feature = "economics")]
+    pub ride_hail: RideHailService,
+    #[cfg(feature = "physics")]
+    pub car_following: CarFollowingModel,
+    #[cfg(feature = "advanced-ai")]
+    pub autonomous: AutonomousFleet,
+    #[cfg(feature = "economics")]
+    pub charging: ChargingNetwork,
+    #[cfg(feature = "physics")]
+    pub drones: DroneFleet,
+    pub plan_schedules: BTreeMap<u32, Vec<ScheduleEntry>>, // Imported plans by agent id
+    pub population_cap: Option<usize>,
+    pub reserve: Vec<(f64, Agent)>, // Despawned agents and when, next to respawn last
+    pub rng: ChaCha8Rng,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl World {
@@ -64,12 +124,38 @@ impl World {
                 roads: Vec::new(),
                 pois: Vec::new(),
                 buildings: Vec::new(),
+                schedule_template: ScheduleTemplate::default(),
             },
             agents: Vec::new(),
-            time: 0.0,
+            clock: SimClock::default(),
+            events: EventQueue::default(),
+            meso: MesoModel::default(),
             day: 0,
-            poi_lookup: HashMap::new(),
-            zone_lookup: HashMap::new(),
+            poi_lookup: BTreeMap::new(),
+            zone_lookup: BTreeMap::new(),
+            agent_lookup: BTreeMap::new(),
+            dirty: BTreeSet::new(),
+            pathfinder: PathFinder::new(&[]),
+            mode_choice: ModeChoiceModel::default(),
+            mode_split: BTreeMap::new(),
+            learning: LearningConfig::default(),
+            convergence: ConvergenceTracker::default(),
+            congestion: CongestionField::default(),
+            emergency: EmergencyService::default(),
+            #[cfg(feature = "economics")]
+            ride_hail: RideHailService::default(),
+            #[cfg(feature = "physics")]
+            car_following: CarFollowingModel::default(),
+            #[cfg(feature = "advanced-ai")]
+            autonomous: AutonomousFleet::default(),
+            #[cfg(feature = "economics")]
+            charging: ChargingNetwork::default(),
+            #[cfg(feature = "physics")]
+            drones: DroneFleet::default(),
+            plan_schedules: BTreeMap::new(),
+            population_cap: None,
+            reserve: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(0),
         }
     }
 
@@ -77,13 +163,30 @@ impl World {
     pub fn load_city(&mut self, city_data: CityModel) {
         self.city = city_data;
         self.build_lookups();
+        self.pathfinder = PathFinder::new(&self.city.roads);
         self.spawn_agents();
+        self.build_agent_lookup();
+        self.deploy_services();
     }
 
     pub fn load_city_with_seed(&mut self, city_data: CityModel, seed: u64) {
         self.city = city_data;
         self.build_lookups();
+        self.pathfinder = PathFinder::new(&self.city.roads);
         self.spawn_agents_with_seed(seed);
+        self.build_agent_lookup();
+        self.deploy_services();
+    }
+
+    /// Sets up every service the build includes for the current city.
+    fn deploy_services(&mut self) {
+        self.emergency.station_fleet(&self.city.pois);
+        #[cfg(feature = "economics")]
+        self.charging.install(&self.city.pois);
+        #[cfg(feature = "physics")]
+        self.drones.deploy(&self.city.pois, &self.city.buildings);
+        #[cfg(feature = "economics")]
+        self.deploy_ride_hail();
     }
 
     fn build_lookups(&mut self) {
@@ -98,6 +201,21 @@ impl World {
         }
     }
 
+    fn build_agent_lookup(&mut self) {
+        self.agent_lookup = self.agents.iter().enumerate().map(|(i, agent)| (agent.id, i)).collect();
+        self.mark_all_dirty();
+    }
+
+    /// Marks every current agent as changed, e.g. for a newcomer who has seen nothing yet.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.extend(self.agents.iter().map(|agent| agent.id));
+    }
+
+    /// Ids of the agents that may have changed since the last call.
+    pub fn take_dirty(&mut self) -> BTreeSet<u32> {
+        std::mem::take(&mut self.dirty)
+    }
+
     #[allow(dead_code)]
     fn spawn_agents(&mut self) {
         let mut agent_id = 0;
@@ -108,8 +226,12 @@ impl World {
             if poi.poi_type == 0 { // HOME
                 let num_agents = (poi.capacity as f32 * 0.3) as u32; // 30% occupancy
                 for _ in 0..num_agents {
+                    let mut rng = rand::thread_rng();
                     let mut agent = Agent::new(agent_id, poi.position.clone());
-                    agent.generate_daily_schedule(&mut rand::thread_rng());
+                    agent.home_poi = Some(poi.id.clone());
+                    let owns_car = rng.gen::<f32>() < self.mode_choice.car_ownership_rate;
+                    self.assign_vehicle(&mut agent, owns_car, &mut rng);
+                    agent.generate_daily_schedule(&self.city.schedule_template, &mut rng);
                     self.agents.push(agent);
                     agent_id += 1;
                 }
@@ -128,33 +250,643 @@ impl World {
                 let num_agents = (poi.capacity as f32 * 0.3) as u32; // 30% occupancy
                 for _ in 0..num_agents {
                     let mut agent = Agent::new(agent_id, poi.position.clone());
-                    agent.generate_daily_schedule(&mut rng);
+                    agent.home_poi = Some(poi.id.clone());
+                    let owns_car = rng.gen::<f32>() < self.mode_choice.car_ownership_rate;
+                    self.assign_vehicle(&mut agent, owns_car, &mut rng);
+                    agent.generate_daily_schedule(&self.city.schedule_template, &mut rng);
                     self.agents.push(agent);
                     agent_id += 1;
                 }
             }
         }
+
+        // Keep drawing from the same stream so later decisions stay reproducible
+        self.rng = rng;
+    }
+
+    #[cfg_attr(not(any(feature = "advanced-ai", feature = "economics")), allow(unused_variables))]
+    fn assign_vehicle(&self, agent: &mut Agent, owns_car: bool, rng: &mut impl Rng) {
+        agent.owns_car = owns_car;
+        #[cfg(feature = "advanced-ai")]
+        {
+            agent.owns_av = agent.owns_car && rng.gen::<f32>() < self.autonomous.config.ownership_rate;
+        }
+        #[cfg(feature = "economics")]
+        {
+            agent.is_ev = agent.owns_car && rng.gen::<f32>() < self.charging.config.ev_share;
+            agent.battery_soc = 0.4 + rng.gen::<f32>() * 0.6;
+        }
+    }
+
+    /// Replaces the generated population with one agent per imported plan.
+    /// Nothing changes if any plan references a POI the city does not have.
+    #[cfg(feature = "scripting")]
+    pub fn load_plans(&mut self, plans: &PlanSet) -> Result<(), String> {
+        let resolved = plans.persons.iter()
+            .map(|person| person.resolve(&self.city.pois, &self.poi_lookup))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut rng = self.rng.clone();
+        self.mark_all_dirty();
+        self.agents.clear();
+        self.events.clear();
+        self.meso.clear();
+        self.reserve.clear();
+        self.plan_schedules.clear();
+
+        for (agent_id, plan) in resolved.into_iter().enumerate() {
+            let agent_id = agent_id as u32;
+            let home = &self.city.pois[self.poi_lookup[&plan.home_poi_id]];
+            let mut agent = Agent::new(agent_id, home.position.clone());
+            agent.home_poi = Some(home.id.clone());
+            agent.current_poi = Some(home.id.clone());
+            agent.person_id = Some(plan.person_id);
+            let owns_car = plan.owns_car.unwrap_or_else(|| rng.gen::<f32>() < self.mode_choice.car_ownership_rate);
+            self.assign_vehicle(&mut agent, owns_car, &mut rng);
+            agent.schedule = plan.schedule.clone();
+            self.plan_schedules.insert(agent_id, plan.schedule);
+            self.agents.push(agent);
+        }
+        self.rng = rng;
+        self.build_agent_lookup();
+
+        // Vehicle state refers to the old agent ids
+        #[cfg(feature = "advanced-ai")]
+        {
+            self.autonomous.empty_trips.clear();
+            self.autonomous.parked.clear();
+        }
+        #[cfg(feature = "economics")]
+        {
+            self.charging.install(&self.city.pois);
+            self.deploy_ride_hail();
+        }
+        Ok(())
     }
 
+    #[cfg(feature = "economics")]
+    fn deploy_ride_hail(&mut self) {
+        let mut anchors: Vec<Point2D> = self.city.roads.iter()
+            .flat_map(|road| road.path.iter().cloned())
+            .collect();
+        if anchors.is_empty() {
+            anchors = self.city.pois.iter().map(|poi| poi.position.clone()).collect();
+        }
+        self.ride_hail.deploy_fleet(&anchors, &mut self.rng);
+    }
+
+    /// Advances the world by `dt` simulated seconds.
     pub fn update(&mut self, dt: f32) {
-        self.time += dt;
+        self.clock.advance(dt);
+        let now = self.clock.elapsed_seconds;
 
         // Handle day transitions
-        if self.time >= 24.0 {
-            self.time -= 24.0;
-            self.day += 1;
+        if self.clock.day() > self.day {
+            self.convergence.close_day(self.day);
+            self.day = self.clock.day();
             self.regenerate_schedules();
         }
 
-        // Update all agents
-        for agent in &mut self.agents {
-            agent.update(dt, self.time);
+        // Agents whose next activity or departure has come round rejoin the update loop
+        self.events.set_population(self.agents.len());
+        for (event, slept) in self.events.pop_due(self.clock.elapsed_seconds) {
+            self.agents[event.agent].catch_up(slept as f32);
+            if matches!(event.kind, EventKind::LinkExit) {
+                self.exit_link(event.agent);
+            }
+        }
+        self.meso.interpolate(&mut self.agents, self.clock.elapsed_seconds);
+        self.dirty.extend(self.meso.legs.keys().map(|&index| self.agents[index].id));
+
+        // Slow down vehicles in busy cells before moving anyone. Only awake agents and those in
+        // the queue model can be on the road; everyone else is asleep at an activity
+        let awake = self.events.awake_agents();
+        self.dirty.extend(awake.iter().map(|&index| self.agents[index].id));
+        self.congestion.rebuild(&self.agents, awake.iter().chain(self.meso.legs.keys()).copied());
+        for &index in &awake {
+            let agent = &mut self.agents[index];
+            agent.speed_factor = if CongestionField::is_motorized(&agent.agent_type) {
+                self.congestion.speed_factor(&agent.position)
+            } else {
+                1.0
+            };
+        }
+        self.emergency.apply_yielding(&mut self.agents, &awake);
+        #[cfg(feature = "physics")]
+        {
+            let highways: Vec<&Road> = self.city.roads.iter().filter(|r| r.road_type == 0).collect(); // HIGHWAY
+            self.car_following.apply(&mut self.agents, &awake, &highways);
+        }
+
+        #[cfg(feature = "economics")]
+        self.update_ride_hail(dt, &awake);
+
+        // Step phase: every awake agent moves using only its own state and read-only world
+        // state, so the order agents are stepped in (or the thread they run on) does not matter
+        let (sim_clock, events, learning) = (&self.clock, &self.events, &self.learning);
+        #[cfg(feature = "economics")]
+        let charging = &self.charging;
+        let departure_lead = learning.departure_lead();
+        let step = |(index, agent): (usize, &mut Agent)| {
+            if events.is_asleep(index) {
+                return None;
+            }
+            #[cfg(feature = "economics")]
+            let (was_traveling, before) = (matches!(agent.state, AgentState::Traveling), agent.position.clone());
+            agent.update(dt, sim_clock, departure_lead);
+
+            #[cfg(feature = "economics")]
+            if was_traveling && ChargingNetwork::uses_battery(agent) {
+                charging.drain(agent, before.distance_to(&agent.position));
+            }
+
+            // Learn from trips that just ended
+            if !matches!(agent.state, AgentState::AtDestination) {
+                return None;
+            }
+            let record = agent.active_trip.take()?;
+            let experienced = (sim_clock.elapsed_seconds - record.departure_time) as f32;
+            agent.travel_memory.learn(&record, experienced);
+            Some((index, record.expected_time, experienced))
+        };
+        #[cfg(feature = "parallel")]
+        let finished: Vec<(usize, f32, f32)> = if crate::platform::can_use_threads() {
+            self.agents.par_iter_mut().enumerate().filter_map(step).collect()
+        } else {
+            self.agents.iter_mut().enumerate().filter_map(step).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let finished: Vec<(usize, f32, f32)> = self.agents.iter_mut().enumerate().filter_map(step).collect();
+
+        // Apply phase: effects on shared state, in agent order so results match a serial run
+        for (index, expected, experienced) in finished {
+            self.convergence.record_trip(expected, experienced);
+            self.after_trip(index);
+        }
+
+        #[cfg(feature = "advanced-ai")]
+        self.update_autonomous(dt, &awake);
+        #[cfg(feature = "economics")]
+        self.charging.update(dt, &self.clock, &mut self.agents, &self.agent_lookup);
+
+        self.emergency.update(dt, now, &self.city.pois, &self.pathfinder, &mut self.rng);
+        #[cfg(feature = "physics")]
+        self.drones.update(dt, now, &self.city.pois, &mut self.rng);
+
+        // Agents that reached a schedule entry need a destination, a route and a mode
+        for index in self.events.awake_agents() {
+            if matches!(self.agents[index].state, AgentState::FindingPath) {
+                self.plan_trip(index);
+            }
+        }
+
+        self.enforce_population_cap();
+        self.hand_off_to_meso();
+        self.sleep_idle_agents();
+    }
+
+    /// Sets how many agents are simulated; `None` brings everyone back.
+    pub fn set_population_cap(&mut self, cap: Option<usize>) {
+        self.population_cap = cap;
+        self.enforce_population_cap();
+    }
+
+    /// Despawns agents from the end of the list while over the cap, each once it is idle so
+    /// nobody vanishes mid-trip, and respawns them in reverse order when the cap allows. Once
+    /// everyone is back, newcomers fill the rest of the cap. Only the tail changes, so every
+    /// other agent keeps its index.
+    fn enforce_population_cap(&mut self) {
+        let cap = self.population_cap.unwrap_or(usize::MAX);
+        let now = self.clock.elapsed_seconds;
+
+        while self.agents.len() > cap && self.agents.last().is_some_and(Agent::is_idle) {
+            let index = self.agents.len() - 1;
+            let slept = self.events.wake(index, now);
+            let Some(mut agent) = self.agents.pop() else { break };
+            self.agent_lookup.remove(&agent.id);
+            self.dirty.insert(agent.id);
+            if let Some(slept) = slept {
+                agent.catch_up(slept as f32);
+            }
+            self.reserve.push((now, agent));
+        }
+
+        while self.agents.len() < cap {
+            let Some((since, mut agent)) = self.reserve.pop() else { break };
+            agent.catch_up((now - since) as f32);
+            self.agent_lookup.insert(agent.id, self.agents.len());
+            self.dirty.insert(agent.id);
+            self.agents.push(agent);
+        }
+        if let Some(cap) = self.population_cap {
+            self.spawn_newcomers(cap);
+        }
+        self.events.set_population(self.agents.len());
+    }
+
+    /// Adds agents at random homes until there are `target`. They start at home and pick up
+    /// their day from its next activity.
+    fn spawn_newcomers(&mut self, target: usize) {
+        let homes: Vec<usize> = self.city.pois.iter().enumerate()
+            .filter(|(_, poi)| poi.poi_type == 0) // HOME
+            .map(|(i, _)| i)
+            .collect();
+        if homes.is_empty() || self.agents.len() >= target {
+            return;
+        }
+
+        let now = self.clock.elapsed_seconds;
+        let mut rng = self.rng.clone();
+        let mut agent_id = self.agent_lookup.keys().next_back().map_or(0, |id| id + 1);
+        while self.agents.len() < target {
+            let home = &self.city.pois[homes[rng.gen_range(0..homes.len())]];
+            let mut agent = Agent::new(agent_id, home.position.clone());
+            agent.home_poi = Some(home.id.clone());
+            agent.current_poi = Some(home.id.clone());
+            let owns_car = rng.gen::<f32>() < self.mode_choice.car_ownership_rate;
+            self.assign_vehicle(&mut agent, owns_car, &mut rng);
+            agent.generate_daily_schedule(&self.city.schedule_template, &mut rng);
+            agent.current_schedule_index = agent.schedule.iter()
+                .take_while(|entry| self.clock.at_hour(entry.start_time) < now)
+                .count();
+
+            self.agent_lookup.insert(agent_id, self.agents.len());
+            self.dirty.insert(agent_id);
+            self.agents.push(agent);
+            agent_id += 1;
+        }
+        self.rng = rng;
+    }
+
+    /// Moves the area of interest, or removes it to simulate everyone in full. Agents in the
+    /// queue model that are now inside it get their full kinematics back straight away.
+    pub fn set_area_of_interest(&mut self, area: Option<AreaOfInterest>) {
+        self.meso.area = area;
+        let now = self.clock.elapsed_seconds;
+        let inside: Vec<usize> = self.meso.legs.iter()
+            .filter(|(_, leg)| self.meso.is_inside(&leg.position_at(now)))
+            .map(|(&index, _)| index)
+            .collect();
+        for index in inside {
+            self.resume_micro(index);
+        }
+    }
+
+    pub fn lod_stats(&self) -> LodStats {
+        let traveling = self.agents.iter().filter(|a| matches!(a.state, AgentState::Traveling)).count();
+        let meso = self.meso.legs.len();
+        LodStats {
+            micro_agents: (traveling - meso) as u32,
+            meso_agents: meso as u32,
+        }
+    }
+
+    /// Travelers well outside the area of interest cross the rest of their link in the queue model.
+    fn hand_off_to_meso(&mut self) {
+        if self.meso.area.is_none() {
+            return;
+        }
+        let now = self.clock.elapsed_seconds;
+        for index in self.events.awake_agents() {
+            let agent = &self.agents[index];
+            if !matches!(agent.state, AgentState::Traveling) || !self.meso.is_outside(&agent.position) {
+                continue;
+            }
+            // An owner who set off without their car stays awake to wait for it
+            #[cfg(feature = "advanced-ai")]
+            if matches!(agent.agent_type, AgentType::Autonomous) && !self.autonomous.vehicle_with_owner(agent.id) {
+                continue;
+            }
+            if let Some(exit_at) = self.meso.enter_link(index, agent, now) {
+                self.events.schedule(index, now, exit_at, EventKind::LinkExit);
+            }
+        }
+    }
+
+    /// Puts a queued agent at the end of its link. It gets one full step from there, which
+    /// handles arrival, before `hand_off_to_meso` decides whether it carries on in the queue.
+    fn exit_link(&mut self, index: usize) {
+        let Some(leg) = self.meso.legs.remove(&index) else { return };
+        let agent = &mut self.agents[index];
+
+        #[cfg(feature = "economics")]
+        if ChargingNetwork::uses_battery(agent) {
+            self.charging.drain(agent, leg.length());
+        }
+        agent.position = leg.to;
+        if !agent.path.is_empty() {
+            agent.path.remove(0);
+        }
+        agent.path_progress = 0.0;
+    }
+
+    /// Takes an agent out of the queue model part-way along its link.
+    fn resume_micro(&mut self, index: usize) {
+        let Some(leg) = self.meso.legs.remove(&index) else { return };
+        let now = self.clock.elapsed_seconds;
+        let fraction = leg.fraction(now);
+        let agent = &mut self.agents[index];
+        self.dirty.insert(agent.id);
+        if let Some(slept) = self.events.wake(index, now) {
+            agent.catch_up(slept as f32);
+        }
+
+        #[cfg(feature = "economics")]
+        if ChargingNetwork::uses_battery(agent) {
+            self.charging.drain(agent, leg.length() * fraction);
+        }
+        agent.position = leg.position_at(now);
+        agent.path_progress = leg.start_progress + (1.0 - leg.start_progress) * fraction;
+    }
+
+    /// Hands an agent that just arrived to the services that look after it there.
+    fn after_trip(&mut self, index: usize) {
+        if !self.start_charging(index) {
+            self.park_if_staying(index);
+        }
+    }
+
+    /// EVs that arrived at a charging station plug in. Returns whether this one did.
+    #[cfg(feature = "economics")]
+    fn start_charging(&mut self, index: usize) -> bool {
+        let agent = &mut self.agents[index];
+        let Some(station) = agent.current_poi.clone().filter(|poi| agent.is_ev && self.charging.is_station(poi)) else {
+            return false;
+        };
+        self.charging.arrive(agent, &station);
+        true
+    }
+
+    #[cfg(not(feature = "economics"))]
+    fn start_charging(&mut self, _index: usize) -> bool {
+        false
+    }
+
+    /// A self-driving car goes home to park if the owner stays a while.
+    #[cfg(feature = "advanced-ai")]
+    fn park_if_staying(&mut self, index: usize) {
+        let agent = &self.agents[index];
+        let min_dwell = (self.autonomous.config.reposition_min_dwell * crate::clock::SECONDS_PER_HOUR) as f64;
+        let staying = agent.schedule.get(agent.current_schedule_index)
+            .is_none_or(|next| self.clock.at_hour(next.start_time) - self.clock.elapsed_seconds >= min_dwell);
+        let home = agent.home_poi.as_ref()
+            .filter(|home| agent.current_poi.as_ref() != Some(*home))
+            .and_then(|home| self.poi_lookup.get(home))
+            .map(|&i| &self.city.pois[i].position);
+        if matches!(agent.agent_type, AgentType::Autonomous) && staying {
+            if let Some(home) = home {
+                self.autonomous.send_to_park(agent.id, &agent.position, home, &self.pathfinder);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "advanced-ai"))]
+    fn park_if_staying(&mut self, _index: usize) {}
+
+    /// Parks agents with nothing to do in the event queue until their next activity.
+    fn sleep_idle_agents(&mut self) {
+        let departure_lead = self.learning.departure_lead();
+        let now = self.clock.elapsed_seconds;
+
+        for index in self.events.awake_agents() {
+            let agent = &self.agents[index];
+            if !agent.is_idle() {
+                continue;
+            }
+            let (wake, kind) = agent.next_event(&self.clock, departure_lead);
+            if wake > now {
+                self.events.schedule(index, now, wake, kind);
+            }
+        }
+    }
+
+    /// Riders waiting for a car are never asleep, so only the awake agents need looking at.
+    #[cfg(feature = "economics")]
+    fn update_ride_hail(&mut self, dt: f32, awake: &[usize]) {
+        let now = self.clock.elapsed_seconds;
+        for &index in awake {
+            let agent = &self.agents[index];
+            if matches!(agent.state, AgentState::AwaitingRide)
+                && matches!(agent.agent_type, AgentType::RideHail)
+                && !self.ride_hail.has_request(agent.id)
+            {
+                let dropoff = agent.path.last().cloned().unwrap_or_else(|| agent.position.clone());
+                self.ride_hail.request(agent.id, agent.position.clone(), dropoff, now);
+            }
+        }
+
+        let events = self.ride_hail.update(dt, now, &self.pathfinder, &self.congestion);
+        for event in events {
+            match event {
+                RideEvent::PickedUp { agent_id } => {
+                    if let Some(agent) = self.agent_mut(agent_id) {
+                        agent.state = AgentState::Riding;
+                    }
+                }
+                RideEvent::Riding { agent_id, position } => {
+                    if let Some(agent) = self.agent_mut(agent_id) {
+                        agent.position = position;
+                    }
+                }
+                RideEvent::DroppedOff { agent_id, position } => {
+                    if let Some(agent) = self.agent_mut(agent_id) {
+                        agent.finish_ride(position);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "advanced-ai")]
+    fn update_autonomous(&mut self, dt: f32, awake: &[usize]) {
+        // Owners about to leave without their car have to wait for it
+        for &index in awake {
+            let agent = &mut self.agents[index];
+            if matches!(agent.state, AgentState::Traveling)
+                && matches!(agent.agent_type, AgentType::Autonomous)
+                && !self.autonomous.vehicle_with_owner(agent.id)
+            {
+                agent.state = AgentState::AwaitingRide;
+                self.autonomous.fetch(agent.id, &agent.position, &self.pathfinder);
+            }
+        }
+
+        for agent_id in self.autonomous.update(dt, &self.congestion) {
+            if let Some(agent) = self.agent_mut(agent_id) {
+                agent.state = AgentState::Traveling;
+            }
+        }
+    }
+
+    /// The agent with this id, unless it is despawned.
+    #[cfg(any(feature = "advanced-ai", feature = "economics"))]
+    fn agent_mut(&mut self, agent_id: u32) -> Option<&mut Agent> {
+        self.agent_lookup.get(&agent_id).map(|&index| &mut self.agents[index])
+    }
+
+    fn plan_trip(&mut self, index: usize) {
+        let agent = &self.agents[index];
+        let Some(entry) = agent.schedule.get(agent.current_schedule_index) else {
+            self.agents[index].state = AgentState::AtDestination;
+            return;
+        };
+
+        let target = entry.preferred_poi_id.as_ref()
+            .and_then(|id| self.poi_lookup.get(id))
+            .map(|&i| &self.city.pois[i])
+            .or_else(|| {
+                if entry.poi_type == 0 { // HOME
+                    agent.home_poi.as_ref()
+                        .and_then(|id| self.poi_lookup.get(id))
+                        .map(|&i| &self.city.pois[i])
+                } else {
+                    None
+                }
+            })
+            .or_else(|| self.find_nearest_poi(&agent.position, entry.poi_type))
+            .map(|poi| (poi.id.clone(), poi.position.clone()));
+
+        let start_time = self.clock.at_hour(entry.start_time);
+        let now = self.clock.elapsed_seconds;
+        let ride_hail_available = self.ride_hail_available();
+        let agent = &mut self.agents[index];
+        agent.current_schedule_index += 1;
+
+        let Some((target_id, target_position)) = target else {
+            // Nothing of that type in the city, skip the activity
+            agent.state = AgentState::AtDestination;
+            return;
+        };
+
+        if agent.current_poi.as_deref() == Some(target_id.as_str()) {
+            agent.state = AgentState::AtDestination;
+            return;
+        }
+
+        let routes = self.pathfinder.find_alternative_paths(
+            &agent.position,
+            &target_position,
+            self.learning.route_alternatives,
+        );
+        let trip = TripContext {
+            network_distance: PathFinder::path_length(&routes[0]),
+            owns_car: agent.owns_car,
+            owns_av: agent.owns_av,
+            ride_hail_available,
+        };
+        let mode = self.mode_choice.choose(&trip, &mut self.rng);
+        let speed = self.mode_choice.params(&mode).map(|p| p.speed_kmh).unwrap_or(5.0);
+        let origin = agent.current_poi.clone().unwrap_or_default();
+        let trip_key = TravelTimeMemory::trip_key(&origin, &target_id);
+
+        // EVs that would arrive nearly empty charge first and come back to this activity afterwards
+        #[cfg(feature = "economics")]
+        if agent.is_ev && matches!(mode, AgentType::Car | AgentType::Autonomous) {
+            let arrival_soc = agent.battery_soc - self.charging.trip_soc(trip.network_distance);
+            let station = self.charging.nearest_station(&agent.position)
+                .filter(|station| agent.current_poi.as_deref() != Some(station.poi_id.as_str()))
+                .map(|station| (station.poi_id.clone(), station.position.clone()));
+
+            if let Some((station_id, station_position)) = station.filter(|_| arrival_soc < self.charging.config.range_anxiety_soc) {
+                agent.current_schedule_index -= 1;
+                self.charging.detours += 1;
+
+                let path = self.pathfinder.find_path(&agent.position, &station_position);
+                let expected_time = self.mode_choice.moving_time(&mode, PathFinder::path_length(&path));
+                let planned = PlannedTrip {
+                    destination: station_id.clone(),
+                    path,
+                    mode,
+                    speed,
+                    record: TripRecord {
+                        trip_key: TravelTimeMemory::trip_key(&origin, &station_id),
+                        mode,
+                        route: 0,
+                        window: self.learning.window_of(now),
+                        departure_time: now,
+                        expected_time,
+                    },
+                };
+
+                agent.plan_trip(planned, now);
+                *self.mode_split.entry(mode).or_insert(0) += 1;
+                return;
+            }
         }
+
+        // Pick route and departure window from what the agent learned on previous days
+        let free_flow: Vec<f32> = routes.iter()
+            .map(|path| self.mode_choice.moving_time(&mode, PathFinder::path_length(path)))
+            .collect();
+        let choice = self.learning.choose(
+            &agent.travel_memory,
+            &trip_key,
+            &mode,
+            start_time,
+            now,
+            &free_flow,
+            &mut self.rng,
+        );
+        if let Some(switched) = agent.travel_memory.remember_choice(&trip_key, choice.route, choice.offset) {
+            self.convergence.record_choice(switched);
+        }
+
+        let planned = PlannedTrip {
+            destination: target_id,
+            path: routes.into_iter().nth(choice.route).unwrap_or_default(),
+            mode,
+            speed,
+            record: TripRecord {
+                trip_key,
+                mode,
+                route: choice.route,
+                window: self.learning.window_of(choice.departure_time),
+                departure_time: choice.departure_time,
+                expected_time: choice.expected_time,
+            },
+        };
+
+        agent.plan_trip(planned, now);
+        *self.mode_split.entry(mode).or_insert(0) += 1;
     }
 
+    #[cfg(feature = "economics")]
+    fn ride_hail_available(&self) -> bool {
+        self.ride_hail.is_available()
+    }
+
+    #[cfg(not(feature = "economics"))]
+    fn ride_hail_available(&self) -> bool {
+        false
+    }
+
+    /// Gives everyone the new day's schedule. Planned trips keep their departure, which is in
+    /// clock seconds; entries of the old day at 24:00 or later that were not reached yet move
+    /// over to the new one.
     fn regenerate_schedules(&mut self) {
-        for agent in &mut self.agents {
-            agent.generate_daily_schedule(&mut rand::thread_rng());
+        let queued: Vec<usize> = self.meso.legs.keys().copied().collect();
+        for index in queued {
+            self.resume_micro(index);
+        }
+        for (index, slept) in self.events.wake_all(self.clock.elapsed_seconds) {
+            self.agents[index].catch_up(slept as f32);
+        }
+        let reserve = self.reserve.iter_mut().map(|(_, agent)| agent);
+        for agent in self.agents.iter_mut().chain(reserve) {
+            let carried: Vec<ScheduleEntry> = agent.schedule.iter()
+                .skip(agent.current_schedule_index)
+                .filter(|entry| entry.start_time >= 24.0)
+                .map(|entry| ScheduleEntry { start_time: entry.start_time - 24.0, ..entry.clone() })
+                .collect();
+
+            match self.plan_schedules.get(&agent.id) {
+                Some(plan) => agent.schedule = plan.clone(),
+                None => agent.generate_daily_schedule(&self.city.schedule_template, &mut self.rng),
+            }
+            if !carried.is_empty() {
+                agent.schedule.splice(0..0, carried);
+                agent.schedule.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+            }
             agent.current_schedule_index = 0;
         }
     }
@@ -0,0 +1,20 @@
+#![cfg(feature = "economics")]
+
+mod common;
+
+#[test]
+fn demand_series_keeps_only_the_latest_intervals() {
+    let mut simulation = common::started(2);
+    simulation.world.charging.config.history_intervals = 8;
+    simulation.fast_forward(30.0);
+
+    let charging = simulation.get_charging_state();
+    let stations = charging.stations.len();
+    assert!(stations > 0);
+    assert_eq!(charging.demand_series.len(), 8 * stations);
+
+    // Seven intervals of a quarter hour, each ending on the first step past it
+    let (first, last) = (&charging.demand_series[0], &charging.demand_series[charging.demand_series.len() - 1]);
+    assert_eq!((last.day, first.day), (1, 1));
+    assert!((1.75..2.0).contains(&(last.time - first.time)), "{} to {}", first.time, last.time);
+}
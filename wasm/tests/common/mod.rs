@@ -0,0 +1,78 @@
+use urbansynth_sim::agent::Point2D;
+use urbansynth_sim::simulation::Simulation;
+use urbansynth_sim::world::{Building, CityModel, Road, Zone, POI};
+
+/// Ticks in one simulated day at the default time scale.
+#[allow(dead_code)]
+pub const TICKS_PER_DAY: usize = 1500;
+
+/// A 2 km square grid town: one highway, residential streets and a POI of every common type.
+pub fn city() -> CityModel {
+    let mut roads = Vec::new();
+    for i in 0..6 {
+        let offset = i as f32 * 400.0;
+        roads.push(Road {
+            id: format!("h{}", i),
+            road_type: if i == 0 { 0 } else { 3 },
+            path: (0..6).map(|j| Point2D::new(j as f32 * 400.0, offset)).collect(),
+            width: 10.0,
+            lanes: 2,
+            speed_limit: 50.0,
+        });
+        roads.push(Road {
+            id: format!("v{}", i),
+            road_type: 3,
+            path: (0..6).map(|j| Point2D::new(offset, j as f32 * 400.0)).collect(),
+            width: 10.0,
+            lanes: 2,
+            speed_limit: 50.0,
+        });
+    }
+
+    let types = [0, 0, 0, 1, 2, 3, 5, 6, 0, 1, 5, 2, 8, 8];
+    let pois = types.iter().enumerate().map(|(k, &poi_type)| POI {
+        id: format!("p{}", k),
+        poi_type,
+        position: Point2D::new((k % 4) as f32 * 600.0 + 50.0, (k / 4) as f32 * 700.0 + 30.0),
+        zone_id: format!("z{}", k % 2),
+        capacity: if poi_type == 8 { 1 } else { 40 },
+    }).collect();
+
+    let zones = vec![
+        Zone {
+            id: "z0".into(),
+            zone_type: 0,
+            boundary: vec![Point2D::new(0.0, 0.0), Point2D::new(2000.0, 0.0), Point2D::new(2000.0, 2000.0)],
+            density: 1.0,
+        },
+        Zone {
+            id: "z1".into(),
+            zone_type: 1,
+            boundary: vec![Point2D::new(0.0, 0.0), Point2D::new(0.0, 2000.0), Point2D::new(2000.0, 2000.0)],
+            density: 1.0,
+        },
+    ];
+    let buildings = vec![Building {
+        id: "b0".into(),
+        footprint: vec![
+            Point2D::new(900.0, 900.0),
+            Point2D::new(1000.0, 900.0),
+            Point2D::new(1000.0, 1000.0),
+            Point2D::new(900.0, 1000.0),
+        ],
+        height: 150.0,
+        zone_id: "z1".into(),
+        building_type: 2,
+    }];
+
+    CityModel { zones, roads, pois, buildings, schedule_template: Default::default() }
+}
+
+/// A running simulation of `city()`.
+#[allow(dead_code)]
+pub fn started(seed: u64) -> Simulation {
+    let mut simulation = Simulation::new_with_seed(seed);
+    simulation.init_with_seed(city(), seed);
+    simulation.start();
+    simulation
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use urbansynth_sim::agent::Point2D;
+use urbansynth_sim::culling::SpatialIndex;
+
+#[test]
+fn invalidate_picks_up_moves_without_a_step() {
+    let mut agents = common::started(5).world.agents.clone();
+    let mut index = SpatialIndex::default();
+    index.update(&agents, 0.0);
+
+    let (far, corner) = (Point2D::new(9000.0, 9000.0), Point2D::new(9100.0, 9100.0));
+    assert!(index.candidates(&far, &corner).is_empty());
+
+    // Same time and population, so only an explicit invalidation rebuilds the grid
+    agents[0].position = Point2D::new(9050.0, 9050.0);
+    index.update(&agents, 0.0);
+    assert!(index.candidates(&far, &corner).is_empty());
+
+    index.invalidate();
+    index.update(&agents, 0.0);
+    assert_eq!(index.candidates(&far, &corner), vec![0]);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::collections::BTreeSet;
+use urbansynth_sim::simulation::Simulation;
+
+type Look = (u32, f32, f32, u8, u8);
+
+fn looks(simulation: &Simulation) -> Vec<Look> {
+    simulation.world.agents.iter()
+        .map(|a| (a.id, a.position.x, a.position.y, a.state.code(), a.agent_type.code()))
+        .collect()
+}
+
+#[test]
+fn delta_lists_every_agent_that_changed_and_skips_dwellers() {
+    let mut simulation = common::started(8);
+    let first = simulation.get_changes_since(0);
+    assert!(first.full);
+    assert_eq!(first.ids.len(), simulation.world.agents.len());
+
+    for _ in 0..common::TICKS_PER_DAY / 3 {
+        simulation.tick();
+    }
+    let (frame, before) = (simulation.get_changes_since(first.frame).frame, looks(&simulation));
+    for _ in 0..20 {
+        simulation.tick();
+    }
+
+    let changed: BTreeSet<u32> = looks(&simulation).iter().zip(&before)
+        .filter(|(now, then)| now != then)
+        .map(|(now, _)| now.0)
+        .collect();
+    let delta = simulation.get_changes_since(frame);
+    let listed: BTreeSet<u32> = delta.ids.iter().copied().collect();
+
+    assert!(!delta.full);
+    assert!(!changed.is_empty());
+    assert!(changed.is_subset(&listed));
+    assert!(listed.len() < simulation.world.agents.len(), "dwelling agents should not be listed");
+}
+
+#[test]
+fn delta_reports_despawns_and_respawns() {
+    let mut simulation = common::started(8);
+    let everyone = simulation.world.agents.len();
+    let frame = simulation.get_changes_since(0).frame;
+
+    simulation.set_population_cap(Some(everyone as u32 - 5));
+    let shrunk = simulation.get_changes_since(frame);
+    let gone: BTreeSet<u32> = shrunk.despawned.iter().copied().collect();
+    assert_eq!(gone.len(), everyone - simulation.world.agents.len());
+    assert!(!gone.is_empty());
+    assert!(gone.iter().all(|id| !simulation.world.agent_lookup.contains_key(id)));
+
+    simulation.set_population_cap(None);
+    let back = simulation.get_changes_since(shrunk.frame);
+    assert_eq!(back.spawned.iter().copied().collect::<BTreeSet<u32>>(), gone);
+    assert!(gone.iter().all(|id| back.ids.contains(id)));
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "physics")]
+
+mod common;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use urbansynth_sim::drone::{DroneFleet, DroneStatus};
+
+#[test]
+fn drop_that_ends_after_midnight_still_returns() {
+    let city = common::city();
+    let mut fleet = DroneFleet::default();
+    fleet.config.drones_per_depot = 1;
+    fleet.config.drop_hours = 0.5;
+    fleet.deploy(&city.pois, &city.buildings);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut now = 23.9 * 3600.0;
+
+    // One certain mission, then none
+    fleet.config.mission_rate = 1.0e9;
+    fleet.update(30.0, now, &city.pois, &mut rng);
+    fleet.config.mission_rate = 0.0;
+    let drone = fleet.drones.iter().position(|d| d.status == DroneStatus::Outbound).expect("no drone took the mission");
+
+    let mut drop_ended_at = None;
+    while now < 27.0 * 3600.0 && fleet.drones[drone].status != DroneStatus::Idle {
+        now += 30.0;
+        fleet.update(30.0, now, &city.pois, &mut rng);
+        if fleet.drones[drone].status == DroneStatus::Delivering {
+            drop_ended_at = Some(fleet.drones[drone].busy_until);
+        }
+    }
+
+    assert!(drop_ended_at.is_some_and(|end| end > 24.0 * 3600.0), "drop ended at {:?}", drop_ended_at);
+    assert_eq!(fleet.drones[drone].status, DroneStatus::Idle);
+    assert!(fleet.drones[drone].mission.is_none());
+    assert_eq!(fleet.metrics.delivered, 1);
+}
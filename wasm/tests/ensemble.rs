@@ -0,0 +1,63 @@
+mod common;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use urbansynth_sim::ensemble::{run_ensemble, EnsembleConfig, Estimate};
+
+fn config(base_seed: u64) -> EnsembleConfig {
+    EnsembleConfig { runs: 3, base_seed, days: 1, sample_interval: 2.0 }
+}
+
+#[test]
+fn same_base_seed_same_report() {
+    let city = common::city();
+    let report = |base_seed| {
+        let report = run_ensemble(&city, &config(base_seed), |_| Ok(())).unwrap();
+        serde_json::to_string(&report).unwrap()
+    };
+
+    assert_eq!(report(40), report(40));
+    assert_ne!(report(40), report(41));
+}
+
+#[test]
+fn runs_are_in_seed_order() {
+    let report = run_ensemble(&common::city(), &config(40), |_| Ok(())).unwrap();
+    let seeds: Vec<u64> = report.runs.iter().map(|run| run.seed).collect();
+
+    assert_eq!(seeds, vec![40, 41, 42]);
+}
+
+#[test]
+fn student_t_interval_on_known_data() {
+    // Mean 5, sample standard deviation sqrt(32 / 7), t(0.975, 7) = 2.365
+    let estimate = Estimate::of([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].into_iter());
+    let half_width = 2.365 * (32.0f64 / 7.0).sqrt() / 8.0f64.sqrt();
+
+    assert!((estimate.mean - 5.0).abs() < 1e-12);
+    assert!((estimate.std_dev - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+    assert!((estimate.ci_low - (5.0 - half_width)).abs() < 1e-9);
+    assert!((estimate.ci_high - (5.0 + half_width)).abs() < 1e-9);
+}
+
+#[test]
+fn student_t_interval_covers_the_true_mean_95_percent_of_the_time() {
+    // Five samples per interval, where a normal-theory interval would only cover about 88%
+    let (true_mean, trials) = (10.0, 4000);
+    let mut rng = ChaCha8Rng::seed_from_u64(2024);
+    let mut normal = move || {
+        // Box-Muller
+        let (u, v): (f64, f64) = (1.0 - rng.gen::<f64>(), rng.gen());
+        true_mean + 3.0 * (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    };
+
+    let covered = (0..trials)
+        .filter(|_| {
+            let estimate = Estimate::of((0..5).map(|_| normal()));
+            estimate.ci_low <= true_mean && true_mean <= estimate.ci_high
+        })
+        .count();
+    let coverage = covered as f64 / trials as f64;
+
+    assert!((0.935..=0.965).contains(&coverage), "coverage {}", coverage);
+}
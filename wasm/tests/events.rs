@@ -0,0 +1,107 @@
+mod common;
+
+use urbansynth_sim::events::{EventKind, EventQueue};
+use urbansynth_sim::simulation::Simulation;
+
+#[test]
+fn only_awake_agents_are_listed() {
+    let mut events = EventQueue::default();
+    events.set_population(4);
+    events.schedule(1, 0.0, 10.0, EventKind::DayStart);
+    events.schedule(3, 0.0, 20.0, EventKind::DayStart);
+    assert_eq!(events.awake_agents(), vec![0, 2]);
+
+    let due = events.pop_due(15.0);
+    assert_eq!(due.len(), 1);
+    assert_eq!(events.awake_agents(), vec![0, 1, 2]);
+
+    events.set_population(3);
+    assert_eq!(events.awake_agents(), vec![0, 1, 2]);
+    assert_eq!(events.stats(3).queued_events, 0);
+    events.set_population(5);
+    assert_eq!(events.awake_agents(), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn early_wake_leaves_no_live_event_behind() {
+    let mut events = EventQueue::default();
+    events.set_population(2);
+    events.schedule(0, 0.0, 10.0, EventKind::ActivityStart);
+    assert_eq!(events.wake(0, 5.0), Some(5.0));
+    assert_eq!(events.stats(2).queued_events, 0);
+
+    // Asleep again until the same time: only the new event may wake it, and only once
+    events.schedule(0, 5.0, 10.0, EventKind::Departure);
+    let due = events.pop_due(10.0);
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].0.kind, EventKind::Departure);
+    assert_eq!(due[0].1, 5.0);
+    assert!(events.pop_due(100.0).is_empty());
+}
+
+#[test]
+fn stale_events_are_swept() {
+    let mut events = EventQueue::default();
+    events.set_population(1000);
+    for agent in 0..1000 {
+        events.schedule(agent, 0.0, 50.0 + agent as f64, EventKind::DayStart);
+    }
+    for agent in 0..600 {
+        events.wake(agent, 1.0);
+    }
+
+    let stats = events.stats(1000);
+    assert_eq!(stats.sleeping_agents, 400);
+    assert_eq!(stats.queued_events, 400);
+    assert_eq!(events.pop_due(2000.0).len(), 400);
+}
+
+fn assert_lookup_matches(simulation: &Simulation) {
+    let world = &simulation.world;
+    assert_eq!(world.agent_lookup.len(), world.agents.len());
+    for (index, agent) in world.agents.iter().enumerate() {
+        assert_eq!(world.agent_lookup.get(&agent.id), Some(&index));
+    }
+}
+
+#[test]
+fn agent_lookup_follows_the_population_cap() {
+    let mut simulation = common::started(5);
+    assert_lookup_matches(&simulation);
+    let everyone = simulation.world.agents.len();
+
+    simulation.set_population_cap(Some(everyone as u32 / 2));
+    for _ in 0..common::TICKS_PER_DAY / 2 {
+        simulation.tick();
+    }
+    assert!(simulation.world.agents.len() < everyone);
+    assert_lookup_matches(&simulation);
+
+    simulation.set_population_cap(None);
+    assert_eq!(simulation.world.agents.len(), everyone);
+    assert_lookup_matches(&simulation);
+}
+
+#[test]
+fn raising_the_cap_past_everyone_spawns_newcomers() {
+    let mut simulation = common::started(5);
+    let everyone = simulation.world.agents.len();
+    for _ in 0..common::TICKS_PER_DAY / 2 {
+        simulation.tick();
+    }
+
+    simulation.set_population_cap(Some(everyone as u32 + 40));
+    assert_eq!(simulation.world.agents.len(), everyone + 40);
+    assert_lookup_matches(&simulation);
+    let newcomers = &simulation.world.agents[everyone..];
+    assert!(newcomers.iter().all(|agent| agent.home_poi.is_some() && agent.current_poi == agent.home_poi));
+    // Half a day in, the morning's activities are behind them
+    assert!(newcomers.iter().all(|agent| agent.current_schedule_index > 0));
+
+    let trips = simulation.world.mode_split.values().sum::<u32>();
+    for _ in 0..common::TICKS_PER_DAY {
+        simulation.tick();
+    }
+    assert!(simulation.world.mode_split.values().sum::<u32>() > trips);
+    assert!(simulation.world.agents[everyone..].iter().any(|agent| agent.current_poi != agent.home_poi));
+}
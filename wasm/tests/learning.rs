@@ -0,0 +1,69 @@
+mod common;
+
+use urbansynth_sim::learning::DayConvergence;
+
+fn mean(days: &[DayConvergence], value: impl Fn(&DayConvergence) -> f32) -> f32 {
+    days.iter().map(value).sum::<f32>() / days.len() as f32
+}
+
+#[test]
+fn expectations_and_choices_settle_over_repeated_days() {
+    for seed in [1, 3, 7] {
+        let mut simulation = common::started(seed);
+        for _ in 0..common::TICKS_PER_DAY * 9 {
+            simulation.tick();
+        }
+
+        let days = simulation.get_learning_convergence();
+        assert!(days.len() >= 8, "seed {}: only {} days closed", seed, days.len());
+
+        // Day 0 has no memory and no previous choices, so compare from day 1 on
+        let (early, late) = (&days[1..3], &days[6..8]);
+        let (early_gap, late_gap) = (mean(early, |d| d.relative_gap), mean(late, |d| d.relative_gap));
+        let (early_switch, late_switch) = (mean(early, |d| d.switch_rate), mean(late, |d| d.switch_rate));
+
+        assert!(late_gap < early_gap, "seed {}: gap went from {} to {}", seed, early_gap, late_gap);
+        assert!(late_switch < early_switch, "seed {}: switch rate went from {} to {}", seed, early_switch, late_switch);
+    }
+}
+
+#[test]
+fn departures_shifted_past_midnight_still_leave() {
+    use urbansynth_sim::agent::{Agent, AgentState, AgentType, PlannedTrip, Point2D};
+    use urbansynth_sim::clock::SimClock;
+    use urbansynth_sim::learning::TripRecord;
+
+    let mut clock = SimClock::default();
+    clock.advance(23.9 * 3600.0);
+    let departure = clock.at_hour(24.2);
+
+    let mut agent = Agent::new(0, Point2D::new(0.0, 0.0));
+    agent.plan_trip(PlannedTrip {
+        destination: "home".into(),
+        path: vec![Point2D::new(0.0, 0.0), Point2D::new(4000.0, 0.0)],
+        mode: AgentType::Car,
+        speed: 30.0,
+        record: TripRecord {
+            trip_key: "bar>home".into(),
+            mode: AgentType::Car,
+            route: 0,
+            window: 96,
+            departure_time: departure,
+            expected_time: 180.0,
+        },
+    }, clock.elapsed_seconds);
+    assert!(matches!(agent.state, AgentState::Waiting));
+    assert_eq!(agent.next_event(&clock, 0.5).0, departure);
+
+    while clock.elapsed_seconds + 30.0 < departure {
+        clock.advance(30.0);
+        agent.update(30.0, &clock, 0.5);
+        assert!(matches!(agent.state, AgentState::Waiting), "left early at {}", clock.elapsed_seconds);
+    }
+    for _ in 0..2 {
+        clock.advance(30.0);
+        agent.update(30.0, &clock, 0.5);
+    }
+    assert!(matches!(agent.state, AgentState::Traveling));
+    assert_eq!(agent.active_trip.as_ref().map(|trip| trip.departure_time), Some(clock.elapsed_seconds - 30.0));
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "networking")]
+
+mod common;
+
+use urbansynth_sim::meso::AreaOfInterest;
+use urbansynth_sim::replay::Recording;
+use urbansynth_sim::simulation::Simulation;
+
+/// A recorded session that uses every kind of input, with the state hash after each tick.
+fn record_session() -> (Simulation, Recording, Vec<String>) {
+    let mut live = common::started(21);
+    live.start_recording(Some(60.0)).unwrap();
+
+    let mut hashes = Vec::new();
+    let mut tick = |live: &mut Simulation, elapsed_ms: Option<f64>| {
+        match elapsed_ms {
+            Some(elapsed_ms) => {
+                live.tick_elapsed(elapsed_ms);
+            }
+            None => live.tick(),
+        }
+        hashes.push(live.state_hash().unwrap().total);
+    };
+
+    for _ in 0..400 {
+        tick(&mut live, None);
+    }
+    live.set_area_of_interest(Some(AreaOfInterest { x: 600.0, y: 600.0, radius: 500.0 }));
+    live.set_speed(2.0);
+    for _ in 0..200 {
+        tick(&mut live, Some(40.0));
+    }
+    live.set_population_cap(Some(30));
+    live.set_update_frequency(30);
+    live.fast_forward(3.0);
+    live.pause();
+    tick(&mut live, None);
+    live.start();
+    live.set_time_scale(120.0);
+    live.set_area_of_interest(None);
+    for _ in 0..200 {
+        tick(&mut live, Some(16.0));
+    }
+
+    let blob = live.stop_recording().unwrap().encode().unwrap();
+    (live, Recording::decode(&blob).unwrap(), hashes)
+}
+
+#[test]
+fn replay_ends_where_the_live_run_did() {
+    let (live, recording, _) = record_session();
+    let replayed = Simulation::replay(&recording, None).unwrap();
+
+    assert_eq!(replayed.state_hash().unwrap(), live.state_hash().unwrap());
+    assert_eq!(replayed.get_lod_stats().meso_agents, live.get_lod_stats().meso_agents);
+}
+
+#[test]
+fn partial_replay_matches_the_live_run_at_that_tick() {
+    let (_, recording, hashes) = record_session();
+
+    for ticks in [1, 400, 450, 601] {
+        let replayed = Simulation::replay(&recording, Some(ticks)).unwrap();
+        assert_eq!(replayed.state_hash().unwrap().total, hashes[ticks - 1], "after {} ticks", ticks);
+    }
+}
+
+#[test]
+fn trajectory_is_sampled_while_recording() {
+    let (_, recording, _) = record_session();
+    let initial = Simulation::load_state(&recording.initial_state).unwrap();
+    let trajectory = recording.trajectory.unwrap();
+
+    // The first frame has everyone, later ones only agents that moved or changed state
+    assert!(trajectory.frames.len() > 1);
+    assert_eq!(trajectory.frames[0].indices.len() as u32, initial.get_agent_count());
+}
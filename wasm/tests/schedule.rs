@@ -0,0 +1,75 @@
+mod common;
+
+use urbansynth_sim::agent::AgentState;
+use urbansynth_sim::schedule::{ActivityTemplate, ScheduleTemplate, TimeDistribution};
+use urbansynth_sim::simulation::Simulation;
+
+fn everyone_home(simulation: &Simulation) -> bool {
+    simulation.world.agents.iter()
+        .all(|agent| matches!(agent.state, AgentState::AtDestination) && agent.current_poi == agent.home_poi)
+}
+
+#[test]
+fn late_night_activities_carry_over_the_day_rollover() {
+    let activity = |name: &str, poi_type, start, duration| ActivityTemplate {
+        name: name.to_string(),
+        poi_type,
+        probability: 1.0,
+        start_time: TimeDistribution::Fixed { value: start },
+        duration: TimeDistribution::Fixed { value: duration },
+        after: Vec::new(),
+    };
+    let mut city = common::city();
+    city.schedule_template = ScheduleTemplate {
+        activities: vec![
+            activity("bar", 3, 23.9, 0.5), // RESTAURANT, departures shift past midnight
+            activity("home", 0, 24.6, 8.0), // HOME, 00:36 the next day
+        ],
+    };
+
+    for seed in [1, 3] {
+        let mut simulation = Simulation::new_with_seed(seed);
+        simulation.init_with_seed(city.clone(), seed);
+        simulation.start();
+
+        for day in 1..=3 {
+            simulation.fast_forward(if day == 1 { 36.0 } else { 24.0 });
+            assert!(everyone_home(&simulation), "seed {}: not everyone home at noon on day {}", seed, day);
+        }
+        let days = simulation.get_learning_convergence();
+        assert!(days[1..].iter().all(|d| d.trips > 0), "seed {}: {:?}", seed, days);
+    }
+}
+
+#[cfg(feature = "scripting")]
+#[test]
+fn plan_times_after_midnight_are_kept() {
+    use urbansynth_sim::plans::{PersonPlan, PlanSet, PlanTime, PlannedActivity};
+
+    let activity = |poi: &str, start: Option<&str>, end: Option<&str>| PlannedActivity {
+        poi_id: poi.to_string(),
+        activity_type: None,
+        start_time: start.map(|time| PlanTime::Clock(time.to_string())),
+        end_time: end.map(|time| PlanTime::Clock(time.to_string())),
+        duration: None,
+    };
+    let plans = PlanSet {
+        persons: (0..6).map(|i| PersonPlan {
+            id: format!("night{}", i),
+            home_poi_id: None,
+            owns_car: Some(i % 2 == 0),
+            activities: vec![
+                activity("p0", None, Some("22:00")),
+                activity("p5", Some("23:50"), Some("25:00")),
+                activity("p0", Some("25:15"), None),
+            ],
+        }).collect(),
+    };
+
+    let mut simulation = common::started(5);
+    simulation.load_plans(&plans).unwrap();
+    for day in 1..=3 {
+        simulation.fast_forward(if day == 1 { 36.0 } else { 24.0 });
+        assert!(everyone_home(&simulation), "not everyone home at noon on day {}", day);
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "networking")]
+
+mod common;
+
+use urbansynth_sim::performance::PerformanceProfile;
+use urbansynth_sim::simulation::Simulation;
+
+#[test]
+fn restored_run_continues_like_an_uninterrupted_one() {
+    let mut uninterrupted = common::started(9);
+    for _ in 0..common::TICKS_PER_DAY / 2 {
+        uninterrupted.tick();
+    }
+
+    let mut restored = Simulation::load_state(&uninterrupted.save_state().unwrap()).unwrap();
+    assert_eq!(restored.state_hash().unwrap(), uninterrupted.state_hash().unwrap());
+
+    // Past a day rollover, so schedules are regenerated from the restored RNG too
+    for _ in 0..common::TICKS_PER_DAY {
+        uninterrupted.tick();
+        restored.tick();
+    }
+    assert_eq!(restored.state_hash().unwrap(), uninterrupted.state_hash().unwrap());
+}
+
+#[test]
+fn profile_is_restored_and_hash_log_is_not() {
+    let mut simulation = common::started(9);
+    let mut profile = PerformanceProfile::for_mobile();
+    profile.set_render_distance(321.0);
+    simulation.set_performance_profile(profile);
+    simulation.start_hash_log(10);
+    for _ in 0..50 {
+        simulation.tick();
+    }
+
+    let restored = Simulation::load_state(&simulation.save_state().unwrap()).unwrap();
+
+    assert_eq!(restored.get_performance_profile().render_distance(), 321.0);
+    assert_eq!(restored.get_performance_profile().max_agents(), PerformanceProfile::for_mobile().max_agents());
+    assert!(restored.get_hash_log().is_none());
+}
+
+#[test]
+fn adaptive_scaling_is_not_restored_but_what_it_applied_is() {
+    let mut simulation = common::started(9);
+    simulation.enable_adaptive_scaling(PerformanceProfile::for_mobile());
+    let (cap, step) = (simulation.world.population_cap, simulation.config.fixed_step_ms);
+
+    let mut restored = Simulation::load_state(&simulation.save_state().unwrap()).unwrap();
+
+    assert_eq!(restored.world.population_cap, cap);
+    assert_eq!(restored.config.fixed_step_ms, step);
+    assert!(!restored.report_frame(5.0, 200.0));
+    assert!(restored.get_scaling_log().is_empty());
+}
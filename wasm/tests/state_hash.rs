@@ -0,0 +1,78 @@
+mod common;
+
+use rand::RngCore;
+use urbansynth_sim::agent::{AgentState, AgentType};
+use urbansynth_sim::simulation::Simulation;
+
+#[test]
+fn same_seed_same_hash() {
+    let (mut a, mut b) = (common::started(11), common::started(11));
+    for _ in 0..200 {
+        a.tick();
+        b.tick();
+    }
+
+    assert_eq!(a.state_hash().unwrap(), b.state_hash().unwrap());
+}
+
+/// Runs until a tick has started with cars on the road, so the congestion grid (a tuple-keyed
+/// map) is filled and has to be part of the hash like everything else.
+fn busy(seed: u64) -> Simulation {
+    let mut simulation = common::started(seed);
+    let driving = |simulation: &Simulation| simulation.world.agents.iter()
+        .any(|agent| agent.agent_type == AgentType::Car && matches!(agent.state, AgentState::Traveling));
+
+    for _ in 0..common::TICKS_PER_DAY {
+        let was_driving = driving(&simulation);
+        simulation.tick();
+        if was_driving {
+            return simulation;
+        }
+    }
+    panic!("nobody drove on the first day");
+}
+
+#[test]
+fn seed_changes_total() {
+    assert_ne!(busy(11).state_hash().unwrap().total, busy(12).state_hash().unwrap().total);
+}
+
+#[test]
+fn rng_draw_changes_total() {
+    let mut simulation = busy(11);
+    let before = simulation.state_hash().unwrap();
+    simulation.world.rng.next_u64();
+    let after = simulation.state_hash().unwrap();
+
+    assert_ne!(before.total, after.total);
+    assert_ne!(before.rng, after.rng);
+    assert_eq!(before.agents, after.agents);
+}
+
+/// The same day stepped on one worker thread and on several ends in the same state.
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_run_matches_serial_run() {
+    let run = |threads: usize| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        pool.install(|| {
+            let mut simulation = common::started(21);
+            for _ in 0..common::TICKS_PER_DAY {
+                simulation.tick();
+            }
+            simulation.state_hash().unwrap()
+        })
+    };
+
+    assert_eq!(run(1), run(4));
+}
+
+#[test]
+fn wall_time_leftovers_are_not_hashed() {
+    let (mut a, mut b) = (common::started(11), common::started(11));
+    let step_ms = a.config.fixed_step_ms;
+    assert_eq!(a.tick_elapsed(step_ms + 5.0), 1);
+    assert_eq!(b.tick_elapsed(step_ms), 1);
+
+    assert_eq!(a.state_hash().unwrap(), b.state_hash().unwrap());
+}